@@ -0,0 +1,275 @@
+use std::collections::{HashMap, VecDeque};
+
+/// The digits as they're written
+pub const DIGITS: &[(&str, u8)] = &[
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+/// The digits spelled out in English
+pub const ENGLISH: &[(&str, u8)] = &[
+    ("zero", 0),
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+/// The English ordinals that stand for a single digit
+pub const ORDINALS: &[(&str, u8)] = &[
+    ("first", 1),
+    ("second", 2),
+    ("third", 3),
+    ("fourth", 4),
+    ("fifth", 5),
+    ("sixth", 6),
+    ("seventh", 7),
+    ("eighth", 8),
+    ("ninth", 9),
+];
+
+#[derive(Debug, Default)]
+struct Node {
+    next: HashMap<u8, usize>,
+    /// The node for the longest suffix of this one that's also in the trie
+    fail: usize,
+    /// The word that ends at this node, as its length and value
+    word: Option<(usize, u8)>,
+    /// The next node down the fail links that ends a word
+    output: Option<usize>,
+}
+
+/// Finds every word from a vocabulary in one pass over a line, using an Aho-Corasick
+/// automaton. Words can overlap, so `eightwo` gives both 8 and 2.
+#[derive(Debug)]
+pub struct Scanner {
+    nodes: Vec<Node>,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &[(&str, u8)]) -> Result<Self, String> {
+        let mut nodes = vec![Node::default()];
+
+        // Build the trie of every word
+        for &(word, value) in vocabulary {
+            if word.is_empty() {
+                return Err("Can't scan for an empty word".to_string());
+            }
+
+            let mut state = 0;
+            for &byte in word.as_bytes() {
+                state = match nodes[state].next.get(&byte) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[state].next.insert(byte, next);
+                        next
+                    }
+                };
+            }
+
+            match nodes[state].word {
+                Some((_, existing)) if existing != value => {
+                    return Err(format!("Word {word} has two values"));
+                }
+                _ => nodes[state].word = Some((word.len(), value)),
+            }
+        }
+
+        // Then link each node to its longest proper suffix, going breadth first so the suffix's
+        // links are always done first
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let children: Vec<(u8, usize)> =
+                nodes[state].next.iter().map(|(&b, &n)| (b, n)).collect();
+            for (byte, child) in children {
+                let fail = if state == 0 {
+                    0
+                } else {
+                    step(&nodes, nodes[state].fail, byte)
+                };
+                nodes[child].fail = fail;
+                nodes[child].output = if nodes[fail].word.is_some() {
+                    Some(fail)
+                } else {
+                    nodes[fail].output
+                };
+                queue.push_back(child);
+            }
+        }
+
+        Ok(Scanner { nodes })
+    }
+
+    /// Lazily finds every word in the line as its byte index and value, in the order they end
+    pub fn matches<'a>(&'a self, line: &'a str) -> Matches<'a> {
+        Matches {
+            nodes: &self.nodes,
+            bytes: line.as_bytes(),
+            position: 0,
+            state: 0,
+            pending: None,
+        }
+    }
+
+    /// Finds the values of the words that start first and last in the line, without keeping
+    /// anything in between. When two words start in the same place, the longer one wins.
+    pub fn first_and_last(&self, line: &str) -> Option<(u8, u8)> {
+        let mut first: Option<(usize, usize, u8)> = None;
+        let mut last: Option<(usize, usize, u8)> = None;
+
+        for (start, len, value) in self.matches(line).with_lengths() {
+            let longer = |(s, l, _): (usize, usize, u8)| s == start && l < len;
+            if first.is_none_or(|f| start < f.0 || longer(f)) {
+                first = Some((start, len, value));
+            }
+            if last.is_none_or(|l| start > l.0 || longer(l)) {
+                last = Some((start, len, value));
+            }
+        }
+
+        Some((first?.2, last?.2))
+    }
+}
+
+/// Iterator over the words found in a line, see [`Scanner::matches`]
+pub struct Matches<'a> {
+    nodes: &'a [Node],
+    bytes: &'a [u8],
+    position: usize,
+    state: usize,
+    /// A node with a word still to be given out that ends at the current position
+    pending: Option<usize>,
+}
+
+impl<'a> Matches<'a> {
+    /// Same as iterating, but with the length of each word as well
+    fn with_lengths(mut self) -> impl Iterator<Item = (usize, usize, u8)> + 'a {
+        std::iter::from_fn(move || self.next_with_length())
+    }
+
+    fn next_with_length(&mut self) -> Option<(usize, usize, u8)> {
+        loop {
+            if let Some(node) = self.pending {
+                let (len, value) = self.nodes[node].word?;
+                self.pending = self.nodes[node].output;
+                return Some((self.position - len, len, value));
+            }
+
+            let &byte = self.bytes.get(self.position)?;
+            self.state = step(self.nodes, self.state, byte);
+            self.position += 1;
+
+            let node = &self.nodes[self.state];
+            self.pending = if node.word.is_some() {
+                Some(self.state)
+            } else {
+                node.output
+            };
+        }
+    }
+}
+
+impl Iterator for Matches<'_> {
+    type Item = (usize, u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_length()
+            .map(|(start, _, value)| (start, value))
+    }
+}
+
+// Private
+
+/// Follows the trie from a node, falling back along the fail links when there's no way forward
+fn step(nodes: &[Node], mut state: usize, byte: u8) -> usize {
+    loop {
+        if let Some(&next) = nodes[state].next.get(&byte) {
+            return next;
+        }
+        if state == 0 {
+            return 0;
+        }
+        state = nodes[state].fail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Scanner {
+        Scanner::new(&[DIGITS, ENGLISH].concat()).unwrap()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let scanner = english();
+        assert_eq!(
+            vec![(0, 8), (4, 2)],
+            scanner.matches("eightwo").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(0, 1), (2, 8), (6, 2), (8, 1), (10, 8)],
+            scanner.matches("oneightwoneight").collect::<Vec<_>>()
+        );
+        assert_eq!(Some((2, 1)), scanner.first_and_last("xtwone3four1"));
+        assert_eq!(None, scanner.first_and_last("abc"));
+        assert_eq!(Some((0, 0)), scanner.first_and_last("zero"));
+    }
+
+    #[test]
+    fn test_nested() {
+        // Words inside other words come out through the output links
+        let scanner = Scanner::new(&[("abcd", 1), ("bc", 2), ("c", 3)]).unwrap();
+        assert_eq!(
+            vec![(1, 2), (2, 3), (0, 1)],
+            scanner.matches("abcd").collect::<Vec<_>>()
+        );
+        assert_eq!(Some((1, 3)), scanner.first_and_last("abcd"));
+    }
+
+    #[test]
+    fn test_vocabularies() {
+        let scanner = Scanner::new(&[DIGITS, ENGLISH, ORDINALS].concat()).unwrap();
+        assert_eq!(Some((7, 9)), scanner.first_and_last("seventhninth"));
+        assert_eq!(Some((4, 2)), scanner.first_and_last("fourthsecond"));
+
+        let german = [
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("fünf", 5),
+            ("sechs", 6),
+            ("sieben", 7),
+            ("acht", 8),
+            ("neun", 9),
+        ];
+        let scanner = Scanner::new(&[DIGITS, &german].concat()).unwrap();
+        assert_eq!(Some((5, 8)), scanner.first_and_last("xfünfeinsacht"));
+        assert_eq!(Some((3, 2)), scanner.first_and_last("dreizwei"));
+        assert_eq!(None, scanner.first_and_last("three"));
+    }
+
+    #[test]
+    fn test_invalid_vocabulary() {
+        assert!(Scanner::new(&[("", 1)]).is_err());
+        assert!(Scanner::new(&[("one", 1), ("one", 2)]).is_err());
+        assert!(Scanner::new(&[("one", 1), ("one", 1)]).is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    /// The character the card is written as
+    pub face: char,
+    /// Position in the ranking, higher is stronger
+    pub rank: usize,
+    /// Only set when the rules say cards come with a suit
+    pub suit: Option<char>,
+}
+
+/// A hand that's been read with a set of [`Rules`](crate::rules::Rules). Hands order by their
+/// hand type first and then card by card, so suits never break a tie.
+#[derive(Debug, Clone)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    /// Position of the hand type in the rules' ladder, higher is stronger
+    pub strength: usize,
+}
+
+impl Hand {
+    fn get_key(&self) -> (usize, Vec<usize>) {
+        (
+            self.strength,
+            self.cards.iter().map(|card| card.rank).collect(),
+        )
+    }
+}
+
+impl PartialEq for Hand {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_key() == other.get_key()
+    }
+}
+
+impl Eq for Hand {}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get_key().cmp(&other.get_key())
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::hand::{Card, Hand};
+
+/// A shape a hand can take, checked as if every wildcard became whatever card suits it best
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// Groups of cards with matching faces, e.g. `[3, 2]` for a full house
+    Groups(Vec<usize>),
+    /// Every card a different rank, all next to each other in the ranking
+    Straight,
+    /// Every card the same suit
+    Flush,
+    /// All of the patterns at once, e.g. a straight flush
+    All(Vec<Pattern>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandType {
+    pub name: &'static str,
+    pub pattern: Pattern,
+}
+
+impl HandType {
+    pub fn new(name: &'static str, pattern: Pattern) -> Self {
+        HandType { name, pattern }
+    }
+}
+
+/// Everything that decides how hands are read and how strong they are
+#[derive(Debug, Clone)]
+pub struct Rules {
+    /// Card faces from weakest to strongest
+    pub ranking: Vec<char>,
+    /// Cards that can stand in for any other card when working out the hand type. They keep
+    /// their own place in the ranking when breaking ties.
+    pub wildcards: Vec<char>,
+    /// How many cards are in a hand
+    pub hand_size: usize,
+    /// Whether each card is followed by its suit, e.g. `AH` for the ace of hearts
+    pub suited: bool,
+    /// Hand types from weakest to strongest. A hand takes the strongest type it can make.
+    pub ladder: Vec<HandType>,
+}
+
+impl Rules {
+    /// Regular Camel Cards, as in part 1
+    pub fn camel_cards() -> Self {
+        Rules {
+            ranking: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            suited: false,
+            ladder: vec![
+                HandType::new("High card", Pattern::Groups(vec![])),
+                HandType::new("One pair", Pattern::Groups(vec![2])),
+                HandType::new("Two pair", Pattern::Groups(vec![2, 2])),
+                HandType::new("Three of a kind", Pattern::Groups(vec![3])),
+                HandType::new("Full house", Pattern::Groups(vec![3, 2])),
+                HandType::new("Four of a kind", Pattern::Groups(vec![4])),
+                HandType::new("Five of a kind", Pattern::Groups(vec![5])),
+            ],
+        }
+    }
+
+    /// Camel Cards where `J` is a joker, the weakest card but wild, as in part 2
+    pub fn camel_cards_with_jokers() -> Self {
+        Rules {
+            ranking: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Rules::camel_cards()
+        }
+    }
+
+    /// Five card poker, where each card is written with its suit, e.g. `AH` for the ace of
+    /// hearts
+    pub fn poker() -> Self {
+        Rules {
+            ranking: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            suited: true,
+            ladder: vec![
+                HandType::new("High card", Pattern::Groups(vec![])),
+                HandType::new("One pair", Pattern::Groups(vec![2])),
+                HandType::new("Two pair", Pattern::Groups(vec![2, 2])),
+                HandType::new("Three of a kind", Pattern::Groups(vec![3])),
+                HandType::new("Straight", Pattern::Straight),
+                HandType::new("Flush", Pattern::Flush),
+                HandType::new("Full house", Pattern::Groups(vec![3, 2])),
+                HandType::new("Four of a kind", Pattern::Groups(vec![4])),
+                HandType::new(
+                    "Straight flush",
+                    Pattern::All(vec![Pattern::Straight, Pattern::Flush]),
+                ),
+            ],
+        }
+    }
+
+    pub fn parse_hand(&self, s: &str) -> Result<Hand> {
+        let chars: Vec<char> = s.chars().collect();
+        let width = if self.suited { 2 } else { 1 };
+        if chars.len() != self.hand_size * width {
+            bail!("Hand {s} should have {} cards", self.hand_size);
+        }
+
+        let cards = chars
+            .chunks(width)
+            .map(|chunk| {
+                let face = chunk[0];
+                let rank = self
+                    .ranking
+                    .iter()
+                    .position(|&c| c == face)
+                    .ok_or_else(|| anyhow!("Unknown card {face} in hand {s}"))?;
+                Ok(Card {
+                    face,
+                    rank,
+                    suit: chunk.get(1).copied(),
+                })
+            })
+            .collect::<Result<Vec<Card>>>()?;
+
+        let strength = self
+            .get_strength(&cards)
+            .ok_or_else(|| anyhow!("Hand {s} doesn't match any hand type"))?;
+
+        Ok(Hand { cards, strength })
+    }
+
+    pub fn get_type(&self, hand: &Hand) -> &HandType {
+        &self.ladder[hand.strength]
+    }
+
+    /// Finds the strongest hand type that the cards can make
+    fn get_strength(&self, cards: &[Card]) -> Option<usize> {
+        let (wild, natural): (Vec<&Card>, Vec<&Card>) = cards
+            .iter()
+            .partition(|card| self.wildcards.contains(&card.face));
+
+        self.ladder
+            .iter()
+            .rposition(|hand_type| self.matches(&hand_type.pattern, &natural, wild.len()))
+    }
+
+    fn matches(&self, pattern: &Pattern, natural: &[&Card], wild: usize) -> bool {
+        match pattern {
+            Pattern::Groups(sizes) => {
+                if sizes.iter().sum::<usize>() > self.hand_size {
+                    return false;
+                }
+
+                let mut counts: HashMap<char, usize> = HashMap::new();
+                for card in natural {
+                    *counts.entry(card.face).or_default() += 1;
+                }
+                let mut counts: Vec<usize> = counts.into_values().collect();
+                counts.sort_unstable_by(|a, b| b.cmp(a));
+                let mut sizes = sizes.clone();
+                sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+                // Filling the largest groups from the largest matches needs the fewest wildcards
+                let needed: usize = sizes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, size)| size.saturating_sub(counts.get(i).copied().unwrap_or(0)))
+                    .sum();
+                needed <= wild
+            }
+            Pattern::Straight => {
+                // Wildcards can't be used as themselves in a straight, so they're left out
+                let order: Vec<char> = self
+                    .ranking
+                    .iter()
+                    .filter(|c| !self.wildcards.contains(c))
+                    .copied()
+                    .collect();
+                if self.hand_size > order.len() {
+                    return false;
+                }
+
+                let mut positions: Vec<usize> = natural
+                    .iter()
+                    .filter_map(|card| order.iter().position(|&c| c == card.face))
+                    .collect();
+                positions.sort_unstable();
+                positions.dedup();
+                if positions.len() != natural.len() {
+                    return false;
+                }
+
+                // The wildcards fill any gaps, so the natural cards only need to fit in a run
+                match (positions.first(), positions.last()) {
+                    (Some(first), Some(last)) => last - first < self.hand_size,
+                    _ => true,
+                }
+            }
+            Pattern::Flush => {
+                self.suited && natural.windows(2).all(|pair| pair[0].suit == pair[1].suit)
+            }
+            Pattern::All(patterns) => patterns
+                .iter()
+                .all(|pattern| self.matches(pattern, natural, wild)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_type_name(rules: &Rules, hand: &str) -> &'static str {
+        rules.get_type(&rules.parse_hand(hand).unwrap()).name
+    }
+
+    #[test]
+    fn test_poker() {
+        let rules = Rules::poker();
+        assert_eq!("High card", get_type_name(&rules, "2H5D9CJSKH"));
+        assert_eq!("Straight", get_type_name(&rules, "9HTDJCQSKH"));
+        assert_eq!("Flush", get_type_name(&rules, "2H5H9HJHKH"));
+        assert_eq!("Full house", get_type_name(&rules, "2H2D2CKSKH"));
+        assert_eq!("Straight flush", get_type_name(&rules, "9HTHJHQHKH"));
+    }
+
+    #[test]
+    fn test_poker_wild() {
+        let rules = Rules {
+            wildcards: vec!['2'],
+            ..Rules::poker()
+        };
+        assert_eq!("One pair", get_type_name(&rules, "2H5D9CJSKH"));
+        assert_eq!("Straight", get_type_name(&rules, "9H2DJCQSKH"));
+        assert_eq!("Straight flush", get_type_name(&rules, "9H2SJHQHKH"));
+        assert_eq!("Four of a kind", get_type_name(&rules, "2H2D2CKSAH"));
+    }
+
+    #[test]
+    fn test_hand_size() {
+        let rules = Rules {
+            hand_size: 3,
+            ..Rules::camel_cards()
+        };
+        assert_eq!("Three of a kind", get_type_name(&rules, "AAA"));
+        assert_eq!("One pair", get_type_name(&rules, "KAK"));
+        assert!(rules.parse_hand("AAAAA").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let rules = Rules::camel_cards();
+        assert!(rules.parse_hand("AA1J2").is_err());
+        assert!(rules.parse_hand("AAJ2").is_err());
+        assert!(rules.parse_hand("").is_err());
+    }
+}
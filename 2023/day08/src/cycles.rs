@@ -0,0 +1,225 @@
+use crate::{gcd, network::Network, Direction};
+
+/// Where a ghost lands on end nodes, once its walk starts repeating itself. The walk repeats
+/// once it's back on the same node at the same point in the instructions.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GhostCycle {
+    /// Steps taken before the walk enters the loop
+    pub offset: u64,
+    /// Steps it takes to go around the loop once
+    pub length: u64,
+    /// Steps that land on an end node before the loop is entered
+    pub early_ends: Vec<u64>,
+    /// Steps that land on an end node on the first time around the loop. Adding any multiple of
+    /// the length to these lands on an end node again.
+    pub ends: Vec<u64>,
+}
+
+impl GhostCycle {
+    pub fn analyse<F>(
+        start: usize,
+        instructions: &[Direction],
+        network: &Network,
+        is_end: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> bool,
+    {
+        // When each (node, instruction) state was first seen, indexed by node then instruction
+        let mut seen: Vec<Option<u64>> = vec![None; network.len() * instructions.len()];
+        let mut ends = Vec::new();
+        let mut node = start;
+        let mut steps = 0u64;
+
+        loop {
+            let index = steps as usize % instructions.len();
+            let state = node * instructions.len() + index;
+            if let Some(first_seen) = seen[state] {
+                // Coming back around to a start that's an end does count, even though the start
+                // itself didn't
+                if first_seen == 0 && is_end(network.get_name(node)) {
+                    ends.push(steps);
+                }
+
+                let (early_ends, ends) = ends.into_iter().partition(|&end| end < first_seen);
+                return GhostCycle {
+                    offset: first_seen,
+                    length: steps - first_seen,
+                    early_ends,
+                    ends,
+                };
+            }
+            seen[state] = Some(steps);
+
+            // Standing on the start doesn't count, only landing on an end after a step
+            if steps > 0 && is_end(network.get_name(node)) {
+                ends.push(steps);
+            }
+
+            node = network.step(node, &instructions[index]);
+            steps += 1;
+        }
+    }
+
+    fn is_end_at(&self, steps: u64) -> bool {
+        if steps < self.offset {
+            return self.early_ends.contains(&steps);
+        }
+
+        self.ends
+            .iter()
+            .any(|&end| steps >= end && (steps - end).is_multiple_of(self.length))
+    }
+
+    /// Whether the only end on the loop is reached exactly once per lap, starting from the very
+    /// first lap, which is when the lcm of the first arrivals gives the right answer.
+    pub fn is_lcm_shortcut(&self) -> bool {
+        self.early_ends.is_empty() && self.ends == [self.length]
+    }
+}
+
+/// Finds the first step where every ghost is on an end node at the same time
+pub fn get_first_simultaneous_end(cycles: &[GhostCycle]) -> Option<u64> {
+    // Before the slowest ghost reaches its loop, it can only be on one of its early ends
+    let slowest = cycles.iter().max_by_key(|cycle| cycle.offset)?;
+    if let Some(&steps) = slowest
+        .early_ends
+        .iter()
+        .find(|&&steps| cycles.iter().all(|cycle| cycle.is_end_at(steps)))
+    {
+        return Some(steps);
+    }
+
+    // After that every ghost is going around its loop, so try every combination of ends
+    let mut combinations: Vec<(u64, u64)> = vec![(0, 1)];
+    for cycle in cycles {
+        combinations = combinations
+            .iter()
+            .flat_map(|&(remainder, modulus)| {
+                cycle
+                    .ends
+                    .iter()
+                    .filter_map(move |&end| crt(remainder, modulus, end, cycle.length))
+            })
+            .collect();
+    }
+
+    // Take the smallest step in each class that every ghost is on its loop for
+    let first = slowest.offset.max(1);
+    combinations
+        .into_iter()
+        .map(|(remainder, modulus)| {
+            if remainder >= first {
+                remainder
+            } else {
+                remainder + (first - remainder).div_ceil(modulus) * modulus
+            }
+        })
+        .min()
+}
+
+// Private
+
+/// Combines `x = a (mod m)` and `x = b (mod n)` into one congruence, when the moduli don't need
+/// to be coprime. Returns the smallest non-negative remainder and the combined modulus.
+fn crt(a: u64, m: u64, b: u64, n: u64) -> Option<(u64, u64)> {
+    let g = gcd(m, n);
+    let (a, b, m, n) = (a as i128, b as i128, m as i128, n as i128);
+    if (b - a) % g as i128 != 0 {
+        return None;
+    }
+
+    // Solve m * k = b - a (mod n) for k using the inverse of m / g
+    let lcm = m / g as i128 * n;
+    let n_g = n / g as i128;
+    let inverse = mod_inverse((m / g as i128).rem_euclid(n_g), n_g)?;
+    let k = ((b - a) / g as i128).rem_euclid(n_g) * inverse % n_g;
+    let x = (a + m * k).rem_euclid(lcm);
+    Some((x as u64, lcm as u64))
+}
+
+fn mod_inverse(a: i128, modulus: i128) -> Option<i128> {
+    if modulus == 1 {
+        return Some(0);
+    }
+
+    // Extended Euclid, only keeping track of the coefficient for a
+    let (mut old_r, mut r) = (a, modulus);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+
+    (old_r == 1).then(|| old_s.rem_euclid(modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(offset: u64, length: u64, early_ends: Vec<u64>, ends: Vec<u64>) -> GhostCycle {
+        GhostCycle {
+            offset,
+            length,
+            early_ends,
+            ends,
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(
+            Some((23, 105)),
+            crt(2, 3, 3, 5).and_then(|(a, m)| crt(a, m, 2, 7))
+        );
+        assert_eq!(Some((10, 12)), crt(4, 6, 2, 4));
+        assert_eq!(None, crt(1, 6, 2, 4));
+    }
+
+    #[test]
+    fn test_analyse() {
+        let input = include_str!("example3.txt");
+        let (instructions, network) = crate::load_input(input).unwrap();
+        let start = network.get_id("11A").unwrap();
+        let cycle_a = GhostCycle::analyse(start, &instructions, &network, crate::is_end_ghost);
+        assert_eq!(cycle(1, 2, vec![], vec![2]), cycle_a);
+        assert!(cycle_a.is_lcm_shortcut());
+
+        let start = network.get_id("22A").unwrap();
+        let cycle_b = GhostCycle::analyse(start, &instructions, &network, crate::is_end_ghost);
+        assert_eq!(cycle(1, 6, vec![], vec![3, 6]), cycle_b);
+        assert!(!cycle_b.is_lcm_shortcut());
+        assert_eq!(Some(6), get_first_simultaneous_end(&[cycle_a, cycle_b]));
+    }
+
+    #[test]
+    fn test_analyse_start_on_end() {
+        let input = "L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)";
+        let (instructions, network) = crate::load_input(input).unwrap();
+        let start = network.get_id("AAA").unwrap();
+        let cycle_a = GhostCycle::analyse(start, &instructions, &network, |name| name == "AAA");
+        assert_eq!(cycle(0, 2, vec![], vec![2]), cycle_a);
+        assert!(cycle_a.is_lcm_shortcut());
+        assert_eq!(Some(2), get_first_simultaneous_end(&[cycle_a]));
+    }
+
+    #[test]
+    fn test_offsets() {
+        // Lands on ends at 5, 9, 13, ... and at 3, 9, 15, ... so the lcm of anything is wrong
+        let cycles = [cycle(2, 4, vec![], vec![5]), cycle(3, 6, vec![], vec![3])];
+        assert_eq!(Some(9), get_first_simultaneous_end(&cycles));
+
+        // Early ends only count before the loop
+        let cycles = [cycle(5, 4, vec![3], vec![6]), cycle(0, 3, vec![], vec![3])];
+        assert_eq!(Some(3), get_first_simultaneous_end(&cycles));
+        let cycles = [cycle(5, 4, vec![2], vec![6]), cycle(0, 4, vec![], vec![3])];
+        assert_eq!(None, get_first_simultaneous_end(&cycles));
+
+        // The lcm shortcut holds when each ghost ends exactly once a lap from the start
+        let cycles = [cycle(0, 4, vec![], vec![4]), cycle(0, 6, vec![], vec![6])];
+        assert!(cycles.iter().all(GhostCycle::is_lcm_shortcut));
+        assert_eq!(Some(12), get_first_simultaneous_end(&cycles));
+    }
+}
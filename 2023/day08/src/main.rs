@@ -1,20 +1,28 @@
-use std::{collections::HashMap, time::Instant};
+use std::{env, time::Instant};
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use cycles::GhostCycle;
+use network::Network;
+
+mod cycles;
+mod network;
 
 type Instructions = Vec<Direction>;
-type NextNode<'a> = (&'a str, &'a str);
-type Map<'a> = HashMap<&'a str, NextNode<'a>>;
 
 enum Direction {
     Left,
     Right,
 }
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Direction {
+    type Error = Error;
+
+    fn try_from(value: char) -> Result<Self> {
         match value {
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            _ => panic!("Invalid value '{value}' for Direction, expected L or R"),
+            'L' => Ok(Direction::Left),
+            'R' => Ok(Direction::Right),
+            _ => bail!("Invalid value '{value}' for Direction, expected L or R"),
         }
     }
 }
@@ -32,50 +40,51 @@ fn lcm(a: u64, b: u64) -> u64 {
     (a * b) / gcd(a, b)
 }
 
-fn load_instructions(line: &str) -> Vec<Direction> {
-    line.chars().map(Direction::from).collect()
-}
-
-fn load_node<'a>(line: &'a str, nodes: &mut Map<'a>) {
-    let id = &line[..3];
-    let next_l = &line[7..10];
-    let next_r = &line[12..15];
-    nodes.insert(id, (next_l, next_r));
+fn load_instructions(line: &str) -> Result<Instructions> {
+    let instructions = line
+        .trim()
+        .chars()
+        .map(Direction::try_from)
+        .collect::<Result<Instructions>>()?;
+    if instructions.is_empty() {
+        bail!("No instructions");
+    }
+    Ok(instructions)
 }
 
-fn load_input(input: &str) -> (Instructions, Map) {
+fn load_input(input: &str) -> Result<(Instructions, Network<'_>)> {
     let mut lines = input.lines();
-    let instructions = load_instructions(lines.next().unwrap());
-    let _ = lines.next(); // throw away empty line
-
-    let mut nodes: Map = HashMap::new();
-    for line in lines {
-        load_node(line, &mut nodes);
-    }
-
-    (instructions, nodes)
+    let instructions = load_instructions(lines.next().ok_or(anyhow!("Empty input"))?)?;
+    let network = Network::parse(lines)?;
+    Ok((instructions, network))
 }
 
-fn step_to_end<F>(start: &str, instructions: &[Direction], map: &Map, is_end: F) -> u64
+/// Counts the steps from the start until landing on an end. Gives up once every node has been
+/// seen at every point in the instructions, since the walk can only go around in circles then.
+fn step_to_end<F>(
+    start: usize,
+    instructions: &[Direction],
+    network: &Network,
+    is_end: F,
+) -> Option<u64>
 where
     F: Fn(&str) -> bool,
 {
-    let mut steps = 0u64;
-    let mut current_node = map.get_key_value(start).unwrap();
-
-    for inst in instructions.iter().cycle() {
-        steps += 1;
-        current_node = match inst {
-            Direction::Left => map.get_key_value(current_node.1 .0).unwrap(),
-            Direction::Right => map.get_key_value(current_node.1 .1).unwrap(),
-        };
-
-        if is_end(current_node.0) {
-            break;
+    let ends: Vec<bool> = network
+        .ids()
+        .map(|id| is_end(network.get_name(id)))
+        .collect();
+    let limit = network.len() * instructions.len();
+
+    let mut current_node = start;
+    for (steps, inst) in (1..=limit).zip(instructions.iter().cycle()) {
+        current_node = network.step(current_node, inst);
+        if ends[current_node] {
+            return Some(steps as u64);
         }
     }
 
-    steps
+    None
 }
 
 fn is_end(name: &str) -> bool {
@@ -86,25 +95,84 @@ fn is_end_ghost(name: &str) -> bool {
     name.ends_with('Z')
 }
 
-fn get_total_steps(input: &str) -> u64 {
-    let (instructions, map) = load_input(input);
-    step_to_end("AAA", &instructions, &map, is_end)
+fn get_total_steps(input: &str) -> Result<u64> {
+    let (instructions, network) = load_input(input)?;
+    let start = network.get_id("AAA")?;
+    network.get_id("ZZZ")?;
+    step_to_end(start, &instructions, &network, is_end).ok_or(anyhow!("Never reaches ZZZ"))
 }
 
-fn get_simultaneous_steps(input: &str) -> u64 {
-    let (instructions, map) = load_input(input);
-    map.keys()
-        .filter(|&id| id.ends_with('A'))
-        .map(|&id| step_to_end(id, &instructions, &map, is_end_ghost))
-        .fold(1u64, lcm)
+fn get_ghost_starts(network: &Network) -> Vec<usize> {
+    network
+        .ids()
+        .filter(|&id| network.get_name(id).ends_with('A'))
+        .collect()
 }
 
-fn main() {
-    let now = Instant::now();
+/// Takes the lcm of each ghost's first arrival. This only works when every ghost arrives at an
+/// end exactly once a lap, see [`GhostCycle::is_lcm_shortcut`].
+fn get_simultaneous_steps_lcm(input: &str) -> Result<u64> {
+    let (instructions, network) = load_input(input)?;
+    get_ghost_starts(&network)
+        .into_iter()
+        .map(|id| {
+            step_to_end(id, &instructions, &network, is_end_ghost).ok_or(anyhow!(
+                "Ghost at {} never reaches an end",
+                network.get_name(id)
+            ))
+        })
+        .try_fold(1u64, |acc, steps| Ok(lcm(acc, steps?)))
+}
+
+fn get_ghost_cycles(input: &str) -> Result<Vec<GhostCycle>> {
+    let (instructions, network) = load_input(input)?;
+    Ok(get_ghost_starts(&network)
+        .into_iter()
+        .map(|id| GhostCycle::analyse(id, &instructions, &network, is_end_ghost))
+        .collect())
+}
 
+fn get_simultaneous_steps(input: &str) -> Result<Option<u64>> {
+    Ok(cycles::get_first_simultaneous_end(&get_ghost_cycles(
+        input,
+    )?))
+}
+
+fn draw(input: &str) -> Result<String> {
+    let (_, network) = load_input(input)?;
+    Ok(network.to_dot(|name| name.ends_with('A') || name.ends_with('Z')))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
-    println!("Part 1: {}", get_total_steps(input));
-    println!("Part 2: {}", get_simultaneous_steps(input));
+
+    if args.get(1).is_some_and(|arg| arg == "dot") {
+        match draw(input) {
+            Ok(dot) => print!("{dot}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
+    // Pass "cycles" to check whether the lcm of each ghost's first end gives part 2
+    if args.get(1).is_some_and(|arg| arg == "cycles") {
+        match get_ghost_cycles(input) {
+            Ok(cycles) if cycles.iter().all(GhostCycle::is_lcm_shortcut) => {
+                println!(
+                    "Lcm shortcut holds: {:?}",
+                    get_simultaneous_steps_lcm(input)
+                );
+            }
+            Ok(cycles) => println!("Lcm shortcut doesn't hold for {:?}", cycles),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
+    let now = Instant::now();
+    println!("Part 1: {:?}", get_total_steps(input));
+    println!("Part 2: {:?}", get_simultaneous_steps(input));
 
     println!("Executed in {} μs", now.elapsed().as_micros());
 }
@@ -116,28 +184,38 @@ mod tests {
     #[test]
     fn test_example1() {
         let input = include_str!("example1.txt");
-        assert_eq!(2, get_total_steps(input));
+        assert_eq!(2, get_total_steps(input).unwrap());
     }
 
     #[test]
     fn test_example2() {
         let input = include_str!("example2.txt");
-        assert_eq!(6, get_total_steps(input));
+        assert_eq!(6, get_total_steps(input).unwrap());
     }
 
     #[test]
     fn test_example3() {
         let input = include_str!("example3.txt");
-        assert_eq!(6, get_simultaneous_steps(input));
+        assert_eq!(Some(6), get_simultaneous_steps(input).unwrap());
+        assert_eq!(6, get_simultaneous_steps_lcm(input).unwrap());
     }
 
     #[test]
     fn test_load_node() {
-        let mut nodes: Map = HashMap::new();
         let input = "AAA = (BBB, CCC)";
+        assert_eq!(("AAA", "BBB", "CCC"), network::parse_node(input).unwrap());
+    }
 
-        load_node(input, &mut nodes);
-
-        assert_eq!(&("BBB", "CCC"), nodes.get("AAA").unwrap());
+    #[test]
+    fn test_invalid_input() {
+        assert!(load_input("LRX\n\nAAA = (AAA, AAA)").is_err());
+        assert!(load_input("").is_err());
+
+        // Part 1 needs both ends, and has to be able to get from one to the other
+        let error = get_total_steps(include_str!("example3.txt")).unwrap_err();
+        assert_eq!("Node AAA is missing", error.to_string());
+        let error = get_total_steps("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)").unwrap_err();
+        assert_eq!("Node ZZZ is missing", error.to_string());
+        assert!(get_total_steps("L\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)").is_err());
     }
 }
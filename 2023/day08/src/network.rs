@@ -0,0 +1,194 @@
+use std::{collections::HashMap, ops::Range};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::Direction;
+
+/// The nodes and where each one leads, with every name swapped for an id so walking the network
+/// is just indexing. Names are borrowed straight from the input.
+#[derive(Debug)]
+pub struct Network<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+    /// The left and right node for each node
+    next: Vec<(usize, usize)>,
+}
+
+impl<'a> Network<'a> {
+    /// Reads lines like `AAA = (BBB, CCC)`. Names can be any length, and spaces around the
+    /// separators don't matter. Blank lines are skipped.
+    pub fn parse<I>(lines: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut names = Vec::new();
+        let mut ids = HashMap::new();
+        let mut links = Vec::new();
+        for line in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (name, left, right) = parse_node(line)?;
+            if ids.insert(name, names.len()).is_some() {
+                bail!("Node {name} is defined more than once");
+            }
+            names.push(name);
+            links.push((left, right));
+        }
+
+        let mut dangling: Vec<&str> = links
+            .iter()
+            .flat_map(|&(left, right)| [left, right])
+            .filter(|name| !ids.contains_key(name))
+            .collect();
+        if !dangling.is_empty() {
+            dangling.sort();
+            dangling.dedup();
+            bail!("Nodes lead to undefined nodes {}", dangling.join(", "));
+        }
+
+        let next = links
+            .iter()
+            .map(|(left, right)| (ids[left], ids[right]))
+            .collect();
+
+        Ok(Network { names, ids, next })
+    }
+
+    pub fn get_id(&self, name: &str) -> Result<usize> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or(anyhow!("Node {name} is missing"))
+    }
+
+    pub fn get_name(&self, id: usize) -> &'a str {
+        self.names[id]
+    }
+
+    pub fn ids(&self) -> Range<usize> {
+        0..self.names.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn step(&self, id: usize, direction: &Direction) -> usize {
+        match direction {
+            Direction::Left => self.next[id].0,
+            Direction::Right => self.next[id].1,
+        }
+    }
+
+    /// Draws the network in Graphviz's DOT format, highlighting the nodes `is_highlighted` picks
+    pub fn to_dot<F>(&self, is_highlighted: F) -> String
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut dot = String::from("digraph network {\n");
+        for (id, &name) in self.names.iter().enumerate() {
+            if is_highlighted(name) {
+                dot += &format!("    \"{name}\" [style=filled];\n");
+            }
+
+            let (left, right) = self.next[id];
+            if left == right {
+                dot += &format!(
+                    "    \"{name}\" -> \"{}\" [label=\"LR\"];\n",
+                    self.names[left]
+                );
+            } else {
+                dot += &format!(
+                    "    \"{name}\" -> \"{}\" [label=\"L\"];\n",
+                    self.names[left]
+                );
+                dot += &format!(
+                    "    \"{name}\" -> \"{}\" [label=\"R\"];\n",
+                    self.names[right]
+                );
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+/// Splits a line like `AAA = (BBB, CCC)` into the node and its left and right nodes
+pub fn parse_node(line: &str) -> Result<(&str, &str, &str)> {
+    let error = || anyhow!("Could not read node from '{line}'");
+
+    let (name, next) = line.split_once('=').ok_or_else(error)?;
+    let (left, right) = next
+        .trim()
+        .strip_prefix('(')
+        .and_then(|next| next.strip_suffix(')'))
+        .and_then(|next| next.split_once(','))
+        .ok_or_else(error)?;
+
+    let names = (name.trim(), left.trim(), right.trim());
+    for name in [names.0, names.1, names.2] {
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "=(),".contains(c)) {
+            return Err(error());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node() {
+        assert_eq!(
+            ("AAA", "BBB", "CCC"),
+            parse_node("AAA = (BBB, CCC)").unwrap()
+        );
+        assert_eq!(
+            ("START", "a", "End2"),
+            parse_node("  START=(a ,End2 )  ").unwrap()
+        );
+        assert!(parse_node("AAA = BBB, CCC").is_err());
+        assert!(parse_node("AAA = (BBB CCC)").is_err());
+        assert!(parse_node(" = (BBB, CCC)").is_err());
+        assert!(parse_node("A A = (BBB, CCC)").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let network = Network::parse(["one = (two, one)", "", "two = (one, one)"]).unwrap();
+        assert_eq!(2, network.len());
+        let one = network.get_id("one").unwrap();
+        let two = network.get_id("two").unwrap();
+        assert_eq!(two, network.step(one, &Direction::Left));
+        assert_eq!(one, network.step(two, &Direction::Right));
+        assert_eq!("two", network.get_name(two));
+        assert!(network.get_id("three").is_err());
+
+        let error = Network::parse(["AAA = (BBB, CCC)", "BBB = (AAA, DDD)"]).unwrap_err();
+        assert_eq!("Nodes lead to undefined nodes CCC, DDD", error.to_string());
+        assert!(Network::parse(["AAA = (AAA, AAA)", "AAA = (AAA, AAA)"]).is_err());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let network =
+            Network::parse(["AAA = (BBB, ZZZ)", "BBB = (AAA, AAA)", "ZZZ = (ZZZ, ZZZ)"]).unwrap();
+        let expected = "\
+digraph network {
+    \"AAA\" [style=filled];
+    \"AAA\" -> \"BBB\" [label=\"L\"];
+    \"AAA\" -> \"ZZZ\" [label=\"R\"];
+    \"BBB\" -> \"AAA\" [label=\"LR\"];
+    \"ZZZ\" [style=filled];
+    \"ZZZ\" -> \"ZZZ\" [label=\"LR\"];
+}
+";
+        assert_eq!(
+            expected,
+            network.to_dot(|name| name == "AAA" || name == "ZZZ")
+        );
+    }
+}
@@ -1,9 +1,10 @@
-use std::{num::ParseIntError, str::FromStr};
-use anyhow::{bail, Error, Result};
+use std::{env, fmt::Display, num::ParseIntError, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
 
 #[derive(Debug)]
 struct Sequence {
-    numbers: Vec<i32>,
+    numbers: Vec<i128>,
 }
 
 impl FromStr for Sequence {
@@ -11,86 +12,239 @@ impl FromStr for Sequence {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Sequence {
             numbers: s
-                .split(' ')
-                .map(|n| n.parse::<i32>())
-                .collect::<Result<Vec<i32>, ParseIntError>>()?,
+                .split_whitespace()
+                .map(|n| n.parse::<i128>())
+                .collect::<Result<Vec<i128>, ParseIntError>>()?,
         })
     }
 }
 
-impl Sequence {
-    fn get_next_number(&self) -> Result<i32> {
-        if self.is_all_zero() {
-            return Ok(0);
-        }
+/// The polynomial that generates a sequence, where `n` is the index into the sequence. Every
+/// coefficient shares the one denominator, with `numerators[i]` going with `n^i`.
+#[derive(Debug, PartialEq, Eq)]
+struct Polynomial {
+    numerators: Vec<i128>,
+    denominator: i128,
+}
+
+impl Polynomial {
+    fn get_degree(&self) -> usize {
+        self.numerators
+            .iter()
+            .rposition(|&n| n != 0)
+            .unwrap_or_default()
+    }
 
-        let next_sequence = self.generate_next_sequence()?;
-        Ok(self.get_last_number()? + next_sequence.get_next_number()?)
+    /// Each coefficient as a fraction in its lowest terms, starting from the constant
+    fn get_coefficients(&self) -> Vec<(i128, i128)> {
+        self.numerators
+            .iter()
+            .map(|&n| {
+                let divisor = gcd(n, self.denominator);
+                (n / divisor, self.denominator / divisor)
+            })
+            .collect()
     }
+}
+
+impl Display for Polynomial {
+    /// Writes the polynomial from the highest power down, e.g. `1/2 n^2 + 3/2 n + 1`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut terms = Vec::new();
+        for (power, (numerator, denominator)) in
+            self.get_coefficients().into_iter().enumerate().rev()
+        {
+            if numerator == 0 {
+                continue;
+            }
 
-    fn get_prev_number(&self) -> Result<i32> {
-        if self.is_all_zero() {
-            return Ok(0);
+            let sign = if numerator < 0 { "-" } else { "+" };
+            let mut term = match (numerator.abs(), denominator) {
+                (1, 1) if power > 0 => String::new(),
+                (numerator, 1) => format!("{numerator} "),
+                (numerator, denominator) => format!("{numerator}/{denominator} "),
+            };
+            match power {
+                0 => term = term.trim_end().to_string(),
+                1 => term += "n",
+                _ => term += &format!("n^{power}"),
+            }
+            terms.push((sign, term));
         }
 
-        let next_sequence = self.generate_next_sequence()?;
-        Ok(self.get_first_number()? - next_sequence.get_prev_number()?)
+        let Some(((first_sign, first), rest)) = terms.split_first() else {
+            return write!(f, "0");
+        };
+        if *first_sign == "-" {
+            write!(f, "-")?;
+        }
+        write!(f, "{first}")?;
+        for (sign, term) in rest {
+            write!(f, " {sign} {term}")?;
+        }
+        Ok(())
     }
+}
 
-    fn get_first_number(&self) -> Result<i32> {
-        let Some(first) = self.numbers.first() else { bail!("Empty sequence.") };
-        Ok(*first)
+impl Sequence {
+    fn get_next_number(&self) -> Result<i128> {
+        self.get_number_at(self.numbers.len() as i128)
     }
 
-    fn get_last_number(&self) -> Result<i32> {
-        let Some(last) = self.numbers.last() else { bail!("Empty sequence.") };
-        Ok(*last)
+    fn get_prev_number(&self) -> Result<i128> {
+        self.get_number_at(-1)
     }
 
-    fn is_all_zero(&self) -> bool {
-        self.numbers.iter().all(|&n| n == 0)
+    /// Works out the number at any index, where the sequence given starts at 0. This uses
+    /// Newton's forward differences, a(n) = sum of C(n, k) * (kth difference at 0).
+    fn get_number_at(&self, index: i128) -> Result<i128> {
+        let mut total = 0i128;
+        let mut binomial = 1i128;
+        for (k, difference) in self.get_leading_differences()?.into_iter().enumerate() {
+            if k > 0 {
+                // C(n, k) = C(n, k - 1) * (n - k + 1) / k, which always divides exactly
+                let k = k as i128;
+                binomial = checked(
+                    index
+                        .checked_sub(k - 1)
+                        .and_then(|n| binomial.checked_mul(n)),
+                )? / k;
+            }
+            total = checked(
+                difference
+                    .checked_mul(binomial)
+                    .and_then(|n| total.checked_add(n)),
+            )?;
+        }
+        Ok(total)
     }
 
-    fn generate_next_sequence(&self) -> Result<Sequence> {
-        let mut nums = self.numbers.iter();
-        let mut next_seq = Vec::<i32>::new();
-        let Some(mut prev) = nums.next() else { bail!("Empty sequence."); };
-        let mut valid_sequence = false;
+    fn get_polynomial(&self) -> Result<Polynomial> {
+        let differences = self.get_leading_differences()?;
+        let degree = differences.len().saturating_sub(1);
+        let denominator = (1..=degree as i128)
+            .try_fold(1i128, |acc, k| acc.checked_mul(k))
+            .ok_or(anyhow!("Overflow working out the polynomial"))?;
+
+        // Each C(n, k) is n(n - 1)...(n - k + 1) / k!, so expand the product one factor at a time
+        let mut numerators = vec![0i128; degree + 1];
+        let mut falling = vec![1i128];
+        let mut factorial = 1i128;
+        for (k, difference) in differences.into_iter().enumerate() {
+            if k > 0 {
+                let root = k as i128 - 1;
+                let mut next = vec![0i128; falling.len() + 1];
+                for (power, &coefficient) in falling.iter().enumerate() {
+                    next[power + 1] = checked(next[power + 1].checked_add(coefficient))?;
+                    next[power] = checked(
+                        coefficient
+                            .checked_mul(root)
+                            .and_then(|n| next[power].checked_sub(n)),
+                    )?;
+                }
+                falling = next;
+                factorial *= k as i128;
+            }
 
-        for num in nums {
-            next_seq.push(num - prev);
-            prev = num;
-            valid_sequence = true;
+            let scale = checked((denominator / factorial).checked_mul(difference))?;
+            for (power, &coefficient) in falling.iter().enumerate() {
+                numerators[power] = checked(
+                    coefficient
+                        .checked_mul(scale)
+                        .and_then(|n| numerators[power].checked_add(n)),
+                )?;
+            }
         }
 
-        if !valid_sequence {
-            bail!("Generated empty sequence.");
+        let divisor = numerators.iter().fold(denominator, |acc, &n| gcd(acc, n));
+        Ok(Polynomial {
+            numerators: numerators.iter().map(|n| n / divisor).collect(),
+            denominator: denominator / divisor,
+        })
+    }
+
+    /// The first number of each row of differences, stopping before the row that's all zero
+    fn get_leading_differences(&self) -> Result<Vec<i128>> {
+        if self.numbers.is_empty() {
+            bail!("Empty sequence.");
         }
 
-        Ok(Sequence { numbers: next_seq })
+        let mut row = self.numbers.clone();
+        let mut leading = Vec::new();
+        while row.iter().any(|&n| n != 0) {
+            if row.len() == 1 {
+                bail!("Differences of {:?} never reach all zero.", self.numbers);
+            }
+
+            leading.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|pair| checked(pair[1].checked_sub(pair[0])))
+                .collect::<Result<Vec<i128>>>()?;
+        }
+
+        Ok(leading)
+    }
+}
+
+fn checked(value: Option<i128>) -> Result<i128> {
+    value.ok_or(anyhow!("Overflow while extrapolating."))
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
     }
+    a.max(1)
 }
 
-fn solution(input: &str) -> Result<i32> {
+fn solution(input: &str) -> Result<i128> {
     let sequences: Vec<Sequence> = input
         .lines()
         .map(|line| line.parse::<Sequence>())
         .collect::<Result<Vec<Sequence>>>()?;
-    
-    sequences.iter().map(|sequence| sequence.get_next_number()).sum()
+
+    sequences
+        .iter()
+        .map(|sequence| sequence.get_next_number())
+        .sum()
 }
 
-fn solution_backwards(input: &str) -> Result<i32> {
+fn solution_backwards(input: &str) -> Result<i128> {
     let sequences: Vec<Sequence> = input
         .lines()
         .map(|line| line.parse::<Sequence>())
         .collect::<Result<Vec<Sequence>>>()?;
-    
-    sequences.iter().map(|sequence| sequence.get_prev_number()).sum()
+
+    sequences
+        .iter()
+        .map(|sequence| sequence.get_prev_number())
+        .sum()
+}
+
+/// Lists the polynomial behind each line of the input
+fn describe(input: &str) -> Result<String> {
+    let mut output = String::new();
+    for line in input.lines() {
+        let polynomial = line.parse::<Sequence>()?.get_polynomial()?;
+        output += &format!("Degree {}: {polynomial}\n", polynomial.get_degree());
+    }
+    Ok(output)
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
+
+    if args.get(1).is_some_and(|arg| arg == "describe") {
+        match describe(input) {
+            Ok(output) => print!("{output}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
     let result = solution(input);
     println!("Part 1: {result:?}");
     let result_pt2 = solution_backwards(input);
@@ -114,4 +268,80 @@ mod tests {
         let result = solution_backwards(input).unwrap();
         assert_eq!(2, result);
     }
+
+    #[test]
+    fn test_get_number_at() {
+        let sequence = "0 3 6 9 12 15".parse::<Sequence>().unwrap();
+        assert_eq!(18, sequence.get_number_at(6).unwrap());
+        assert_eq!(
+            3_000_000_000_000_000_000,
+            sequence.get_number_at(1_000_000_000_000_000_000).unwrap()
+        );
+        assert_eq!(-30, sequence.get_number_at(-10).unwrap());
+
+        // (n + 1)(n + 2) / 2
+        let sequence = "1 3 6 10 15 21".parse::<Sequence>().unwrap();
+        assert_eq!(0, sequence.get_number_at(-1).unwrap());
+        assert_eq!(0, sequence.get_number_at(-2).unwrap());
+        assert_eq!(1, sequence.get_number_at(-3).unwrap());
+        assert_eq!(
+            500_000_000_500_000_000,
+            sequence.get_number_at(999_999_999).unwrap()
+        );
+        assert!(sequence.get_number_at(i128::MAX / 2).is_err());
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let polynomial = "1 3 6 10 15 21"
+            .parse::<Sequence>()
+            .unwrap()
+            .get_polynomial()
+            .unwrap();
+        assert_eq!(2, polynomial.get_degree());
+        assert_eq!(vec![(1, 1), (3, 2), (1, 2)], polynomial.get_coefficients());
+        assert_eq!("1/2 n^2 + 3/2 n + 1", polynomial.to_string());
+
+        let polynomial = "10 13 16 21 30 45"
+            .parse::<Sequence>()
+            .unwrap()
+            .get_polynomial()
+            .unwrap();
+        assert_eq!(3, polynomial.get_degree());
+        assert_eq!("1/3 n^3 - n^2 + 11/3 n + 10", polynomial.to_string());
+
+        let polynomial = "4 4 4"
+            .parse::<Sequence>()
+            .unwrap()
+            .get_polynomial()
+            .unwrap();
+        assert_eq!(0, polynomial.get_degree());
+        assert_eq!("4", polynomial.to_string());
+        assert_eq!(
+            "0",
+            "0 0"
+                .parse::<Sequence>()
+                .unwrap()
+                .get_polynomial()
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "-2 n",
+            "0 -2 -4"
+                .parse::<Sequence>()
+                .unwrap()
+                .get_polynomial()
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_never_zero() {
+        let sequence = "1 2 4 8 16".parse::<Sequence>().unwrap();
+        assert!(sequence.get_next_number().is_err());
+        assert!(sequence.get_polynomial().is_err());
+        assert!("".parse::<Sequence>().unwrap().get_next_number().is_err());
+    }
 }
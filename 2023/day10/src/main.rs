@@ -1,6 +1,12 @@
-use std::{cell::RefCell, collections::HashSet, fmt::Debug, str::FromStr};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashSet,
+    env,
+    fmt::Debug,
+    str::FromStr,
+};
 
-use anyhow::{bail, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 enum Direction {
@@ -20,6 +26,16 @@ impl Direction {
         }
     }
 
+    /// Every direction, in the order they're tried when leaving the starting tile
+    fn all() -> [Direction; 4] {
+        [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ]
+    }
+
     fn step(&self) -> (i8, i8) {
         match &self {
             Direction::North => (-1, 0),
@@ -86,16 +102,79 @@ impl Tile {
     }
 }
 
+/// Gives the pipe that connects the two directions, both as it's written in the input and as a
+/// box-drawing character.
+fn get_pipe(connecting: &HashSet<Direction>) -> Option<(char, char)> {
+    let has = |direction| connecting.contains(&direction);
+    if connecting.len() != 2 {
+        return None;
+    }
+
+    Some(
+        match (
+            has(Direction::North),
+            has(Direction::East),
+            has(Direction::South),
+        ) {
+            (true, _, true) => ('|', '│'),
+            (true, true, _) => ('L', '└'),
+            (true, _, _) => ('J', '┘'),
+            (_, true, true) => ('F', '┌'),
+            (_, true, _) => ('-', '─'),
+            _ => ('7', '┐'),
+        },
+    )
+}
+
 impl PartialEq for Tile {
     fn eq(&self, other: &Self) -> bool {
         self.x == other.x && self.y == other.y
     }
 }
 
+/// The loop through the maze, starting and ending at the starting tile
+struct Loop {
+    /// Every tile on the loop as (x, y), in the order they're walked
+    tiles: Vec<(usize, usize)>,
+    /// The directions the starting tile actually connects to
+    start_connecting: HashSet<Direction>,
+}
+
+impl Loop {
+    /// The tiles where the loop turns, in order. Together they make up the loop as a polygon.
+    fn get_vertices(&self) -> Vec<(usize, usize)> {
+        let len = self.tiles.len();
+        (0..len)
+            .filter(|&i| {
+                let prev = self.tiles[(i + len - 1) % len];
+                let next = self.tiles[(i + 1) % len];
+                prev.0 != next.0 && prev.1 != next.1
+            })
+            .map(|i| self.tiles[i])
+            .collect()
+    }
+
+    /// Twice the area enclosed by the loop, using the shoelace formula
+    fn get_double_area(&self) -> usize {
+        let vertices = self.get_vertices();
+        let sum: i64 = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(&(x1, y1), &(x2, y2))| x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64)
+            .sum();
+        sum.unsigned_abs() as usize
+    }
+
+    /// Tiles enclosed by the loop, from Pick's theorem: A = i + b/2 - 1
+    fn count_inside(&self) -> usize {
+        (self.get_double_area() + 2 - self.tiles.len()) / 2
+    }
+}
+
 struct Map {
     grid: Vec<Vec<Tile>>,
     starting_tile: Tile,
-    loop_traced: RefCell<bool>,
+    traced_loop: RefCell<Option<Loop>>,
 }
 
 impl FromStr for Map {
@@ -120,7 +199,7 @@ impl FromStr for Map {
             Ok(Map {
                 grid,
                 starting_tile,
-                loop_traced: RefCell::from(false),
+                traced_loop: RefCell::from(None),
             })
         } else {
             bail!("Could not find starting tile.")
@@ -132,50 +211,115 @@ impl Map {
     fn trace_loop(&self) -> Result<()> {
         // This will walk the loop and mark all tiles with whether they're on the loop.
         // If we already traced the loop, then return.
-        if *self.loop_traced.borrow() {
+        if self.traced_loop.borrow().is_some() {
             return Ok(());
         }
 
-        // Start with the starting tile's first valid connection
-        let mut direction = *self
-            .get_valid_connections(&self.starting_tile)
-            .iter()
-            .next()
-            .ok_or(Error::msg(format!(
-                "Could not find valid connection for tile {:?}",
-                self.starting_tile
-            )))?;
+        // The starting tile could have pipes pointing at it that aren't part of the loop, so try
+        // each connection until one makes it back around
+        let valid_connections = self.get_valid_connections(&self.starting_tile);
+        let mut result = Err(Error::msg(format!(
+            "Could not find valid connection for tile {:?}",
+            self.starting_tile
+        )));
+        for direction in Direction::all() {
+            if valid_connections.contains(&direction) {
+                result = self.walk_loop(direction);
+                if result.is_ok() {
+                    break;
+                }
+            }
+        }
+        let traced_loop = result?;
 
-        // Get the adjacent tile
-        let mut current_tile = self
-            .get_adjacent_tile(&self.starting_tile, direction)
-            .ok_or(Error::msg(format!(
-                "Could not find adjacent for tile {:?}",
-                self.starting_tile
-            )))?;
+        for &(x, y) in &traced_loop.tiles {
+            *self.grid[x][y].is_on_loop.borrow_mut() = true;
+        }
 
-        // Mark the current tile as on the loop
-        *current_tile.is_on_loop.borrow_mut() = true;
+        // Finally, keep the loop so it doesn't need to be traced again.
+        *self.traced_loop.borrow_mut() = Some(traced_loop);
+
+        Ok(())
+    }
+
+    /// Walks the loop, leaving the starting tile in the given direction
+    fn walk_loop(&self, first_direction: Direction) -> Result<Loop> {
+        let mut tiles = vec![(self.starting_tile.x, self.starting_tile.y)];
+        let mut direction = first_direction;
+        let mut current_tile = &self.starting_tile;
 
         // Repeat until we're back at the starting tile
-        while *current_tile != self.starting_tile {
-            direction = current_tile.next_direction(direction.reverse())?;
+        loop {
             current_tile = self
                 .get_adjacent_tile(current_tile, direction)
                 .ok_or(Error::msg(format!(
                     "Could not find adjacent tile for tile {:?}",
                     current_tile
                 )))?;
-            *current_tile.is_on_loop.borrow_mut() = true;
+            if *current_tile == self.starting_tile {
+                break;
+            }
+
+            tiles.push((current_tile.x, current_tile.y));
+            let from = direction.reverse();
+            if !current_tile.connecting.contains(&from) {
+                bail!("Tile {:?} doesn't connect back", current_tile);
+            }
+            direction = current_tile.next_direction(from)?;
         }
 
-        // Finally, indicate that the loop has been traced.
-        *self.loop_traced.borrow_mut() = true;
+        Ok(Loop {
+            tiles,
+            start_connecting: HashSet::from([first_direction, direction.reverse()]),
+        })
+    }
 
-        Ok(())
+    fn get_loop(&self) -> Result<Ref<'_, Loop>> {
+        self.trace_loop()?;
+        Ref::filter_map(self.traced_loop.borrow(), |traced| traced.as_ref())
+            .map_err(|_| anyhow!("Loop was not traced"))
+    }
+
+    /// Works out which pipe is hiding under the starting tile
+    fn get_start_pipe(&self) -> Result<char> {
+        let traced_loop = self.get_loop()?;
+        get_pipe(&traced_loop.start_connecting)
+            .map(|(pipe, _)| pipe)
+            .ok_or(anyhow!("Starting tile doesn't have two connections"))
     }
 
-    fn get_adjacent_tile<'a>(&'a self, tile: &'a Tile, direction: Direction) -> Option<&Tile> {
+    /// Draws the maze with the loop in box-drawing characters, and every other tile marked as
+    /// either inside (I) or outside (O) of it.
+    fn render(&self) -> Result<String> {
+        let traced_loop = self.get_loop()?;
+        let mut output = String::new();
+
+        for row in &self.grid {
+            // Scan across the row, crossing in or out of the loop at each pipe that goes north
+            let mut inside = false;
+            for tile in row {
+                if !*tile.is_on_loop.borrow() {
+                    output.push(if inside { 'I' } else { 'O' });
+                    continue;
+                }
+
+                let connecting = if *tile == self.starting_tile {
+                    &traced_loop.start_connecting
+                } else {
+                    &tile.connecting
+                };
+                if connecting.contains(&Direction::North) {
+                    inside = !inside;
+                }
+                output.push(get_pipe(connecting).map_or('?', |(_, drawn)| drawn));
+            }
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+
+    fn get_adjacent_tile<'a>(&'a self, tile: &'a Tile, direction: Direction) -> Option<&'a Tile> {
         let (dx, dy) = direction.step();
 
         let new_x = tile.x as i32 + dx as i32;
@@ -218,28 +362,7 @@ impl Map {
     }
 
     fn count_inside_tiles(&self) -> Result<usize> {
-        self.trace_loop()?;
-        Ok(self.count(|tile| self.is_tile_inside(tile)))
-    }
-
-    fn is_tile_inside(&self, tile: &Tile) -> bool {
-        // If tile is on the loop, return false
-        if *tile.is_on_loop.borrow() {
-            return false;
-        }
-
-        // Use the ray algorithm to count how many intersections are on a ray to the edge
-        let row = &self.grid[tile.x][..tile.y];
-        let intersections = row
-            .iter()
-            .filter(|&t| {
-                // We just need to count number of tiles on the loop that has a northern connection.
-                *t.is_on_loop.borrow() && self.get_valid_connections(t).contains(&Direction::North)
-            })
-            .count();
-
-        // If odd, the tile is inside
-        intersections % 2 == 1
+        Ok(self.get_loop()?.count_inside())
     }
 }
 
@@ -254,11 +377,28 @@ fn solution_part2(input: &str) -> Result<usize> {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
+
+    if args.get(1).is_some_and(|arg| arg == "draw") {
+        let drawing = input.parse::<Map>().and_then(|map| {
+            Ok(format!(
+                "{}Starting pipe: {}\n",
+                map.render()?,
+                map.get_start_pipe()?
+            ))
+        });
+        match drawing {
+            Ok(drawing) => print!("{drawing}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
     let result_pt1 = solution_part1(input);
     println!("Part 1: {result_pt1:?}");
     let result_pt2 = solution_part2(input);
-    println!("Part 1: {result_pt2:?}");
+    println!("Part 2: {result_pt2:?}");
 }
 
 #[cfg(test)]
@@ -292,4 +432,55 @@ mod tests {
         let result = solution_part2(input).unwrap();
         assert_eq!(10, result);
     }
+
+    #[test]
+    fn test_start_pipe() {
+        let expected = [('1', 'F'), ('2', 'F'), ('3', 'F'), ('4', '7')];
+        for (example, pipe) in expected {
+            let input = match example {
+                '1' => include_str!("example1.txt"),
+                '2' => include_str!("example2.txt"),
+                '3' => include_str!("example3.txt"),
+                _ => include_str!("example4.txt"),
+            };
+            let map = input.parse::<Map>().unwrap();
+            assert_eq!(pipe, map.get_start_pipe().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_vertices() {
+        let map = include_str!("example1.txt").parse::<Map>().unwrap();
+        let traced_loop = map.get_loop().unwrap();
+        assert_eq!(
+            vec![(1, 1), (1, 3), (3, 3), (3, 1)],
+            traced_loop.get_vertices()
+        );
+        assert_eq!(8, traced_loop.get_double_area());
+        assert_eq!(1, traced_loop.count_inside());
+    }
+
+    #[test]
+    fn test_render() {
+        let map = include_str!("example3.txt").parse::<Map>().unwrap();
+        let expected = "\
+OOOOOOOOOOO
+O┌───────┐O
+O│┌─────┐│O
+O││OOOOO││O
+O││OOOOO││O
+O│└─┐O┌─┘│O
+O│II│O│II│O
+O└──┘O└──┘O
+OOOOOOOOOOO
+";
+        assert_eq!(expected, map.render().unwrap());
+
+        // The inside tiles drawn match the count from the area
+        for input in [include_str!("example4.txt"), include_str!("input.txt")] {
+            let map = input.parse::<Map>().unwrap();
+            let drawn = map.render().unwrap().chars().filter(|&c| c == 'I').count();
+            assert_eq!(map.count_inside_tiles().unwrap(), drawn);
+        }
+    }
 }
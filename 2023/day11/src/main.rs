@@ -1,4 +1,4 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{collections::HashSet, env, str::FromStr};
 
 use anyhow::{bail, Error, Result};
 
@@ -8,13 +8,6 @@ struct Point {
     y: usize,
 }
 
-impl Point {
-    fn get_distance(&self, other: &Point) -> usize {
-        (self.x as isize - other.x as isize).unsigned_abs()
-            + (self.y as isize - other.y as isize).unsigned_abs()
-    }
-}
-
 #[derive(Debug)]
 struct Map {
     galaxies: Vec<Point>,
@@ -56,33 +49,198 @@ impl FromStr for Map {
     }
 }
 
+/// How wide an empty row or column becomes once the universe expands
+#[derive(Clone, Debug)]
+enum Weight {
+    /// Every empty line becomes this wide
+    Uniform(f64),
+    /// Each empty line has its own width, in order from the top or left. Any empty lines past
+    /// the end stay as they are.
+    PerLine(Vec<f64>),
+}
+
+impl FromStr for Weight {
+    type Err = Error;
+
+    /// Either a single width for every line, or a width for each line separated by commas
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let widths = s
+            .split(',')
+            .map(|width| width.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()?;
+        Ok(match widths[..] {
+            [width] => Weight::Uniform(width),
+            _ => Weight::PerLine(widths),
+        })
+    }
+}
+
+impl Weight {
+    fn get(&self, index: usize) -> f64 {
+        match self {
+            Weight::Uniform(width) => *width,
+            Weight::PerLine(widths) => widths.get(index).copied().unwrap_or(1.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Expansion {
+    rows: Weight,
+    cols: Weight,
+}
+
 impl Map {
+    /// Sums the distance between every pair of galaxies. Distances along each axis are summed
+    /// separately, since once sorted each galaxy is the same distance past all the ones before.
     fn get_sum_paths(&self, factor: usize) -> usize {
         let galaxies = self.get_expanded_galaxies(factor);
-        galaxies.iter().enumerate().fold(0, |acc, (i, galaxy)| {
-            acc + galaxies[..i]
-                .iter()
-                .map(|other_galaxy| galaxy.get_distance(other_galaxy))
-                .sum::<usize>()
-        })
+        sum_differences(galaxies.iter().map(|galaxy| galaxy.x).collect())
+            + sum_differences(galaxies.iter().map(|galaxy| galaxy.y).collect())
+    }
+
+    /// Like `get_sum_paths`, but for widths that needn't be whole or the same for every line
+    fn get_sum_paths_with(&self, expansion: &Expansion) -> f64 {
+        let galaxies = self.get_expanded_positions(expansion);
+        sum_weighted_differences(galaxies.iter().map(|&(x, _)| x).collect())
+            + sum_weighted_differences(galaxies.iter().map(|&(_, y)| y).collect())
     }
 
     fn get_expanded_galaxies(&self, factor: usize) -> Vec<Point> {
-        let mut new_galaxies = Vec::new();
-
-        for galaxy in self.galaxies.iter() {
-            new_galaxies.push(Point {
-                x: galaxy.x
-                    + self.empty_cols.iter().filter(|&&c| c < galaxy.x).count() * (factor - 1),
-                y: galaxy.y
-                    + self.empty_rows.iter().filter(|&&r| r < galaxy.y).count() * (factor - 1),
-            });
+        let count_before = |empty: &HashSet<usize>, position: usize| {
+            empty.iter().filter(|&&line| line < position).count()
+        };
+
+        self.galaxies
+            .iter()
+            .map(|galaxy| Point {
+                x: galaxy.x + count_before(&self.empty_cols, galaxy.x) * (factor - 1),
+                y: galaxy.y + count_before(&self.empty_rows, galaxy.y) * (factor - 1),
+            })
+            .collect()
+    }
+
+    /// Gives each galaxy's (x, y) once the empty rows and columns have been widened
+    fn get_expanded_positions(&self, expansion: &Expansion) -> Vec<(f64, f64)> {
+        let col_offsets = Offsets::new(&self.empty_cols, &expansion.cols);
+        let row_offsets = Offsets::new(&self.empty_rows, &expansion.rows);
+
+        self.galaxies
+            .iter()
+            .map(|galaxy| {
+                (
+                    galaxy.x as f64 + col_offsets.get(galaxy.x),
+                    galaxy.y as f64 + row_offsets.get(galaxy.y),
+                )
+            })
+            .collect()
+    }
+
+    /// Finds the two galaxies closest together once expanded, and how far apart they are
+    fn get_nearest_pair(&self, expansion: &Expansion) -> Option<(Point, Point, f64)> {
+        let positions = self.get_expanded_positions(expansion);
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_by(|&a, &b| positions[a].0.total_cmp(&positions[b].0));
+
+        // Sweep across, only looking back while the x distance alone could still beat the best
+        let mut nearest: Option<(usize, usize, f64)> = None;
+        for (i, &a) in order.iter().enumerate() {
+            for &b in order[..i].iter().rev() {
+                let dx = positions[a].0 - positions[b].0;
+                if nearest.is_some_and(|(_, _, best)| dx >= best) {
+                    break;
+                }
+
+                let distance = dx + (positions[a].1 - positions[b].1).abs();
+                if nearest.is_none_or(|(_, _, best)| distance < best) {
+                    nearest = Some((b, a, distance));
+                }
+            }
         }
 
-        new_galaxies
+        nearest
+            .map(|(a, b, distance)| (self.galaxies[a].clone(), self.galaxies[b].clone(), distance))
+    }
+
+    /// Finds the two galaxies farthest apart once expanded, and how far apart they are
+    fn get_farthest_pair(&self, expansion: &Expansion) -> Option<(Point, Point, f64)> {
+        let positions = self.get_expanded_positions(expansion);
+
+        // Rotating by 45 degrees turns the distance into the larger difference along either
+        // diagonal, so the farthest pair is at the ends of one of them
+        type Diagonal = fn(&(f64, f64)) -> f64;
+        let diagonals: [Diagonal; 2] = [|&(x, y)| x + y, |&(x, y)| x - y];
+        diagonals
+            .iter()
+            .filter_map(|diagonal| {
+                let by_diagonal = |&a: &usize, &b: &usize| {
+                    diagonal(&positions[a]).total_cmp(&diagonal(&positions[b]))
+                };
+                let min = (0..positions.len()).min_by(by_diagonal)?;
+                let max = (0..positions.len()).max_by(by_diagonal)?;
+                Some((
+                    min,
+                    max,
+                    diagonal(&positions[max]) - diagonal(&positions[min]),
+                ))
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(a, b, distance)| (self.galaxies[a].clone(), self.galaxies[b].clone(), distance))
     }
 }
 
+/// How far each position along an axis gets pushed out by the empty lines before it
+struct Offsets {
+    /// The empty lines in order
+    lines: Vec<usize>,
+    /// The total extra width of the empty lines up to and including each one
+    extra: Vec<f64>,
+}
+
+impl Offsets {
+    fn new(empty: &HashSet<usize>, weight: &Weight) -> Self {
+        let mut lines: Vec<usize> = empty.iter().copied().collect();
+        lines.sort_unstable();
+        let extra = (0..lines.len())
+            .scan(0.0, |total, index| {
+                *total += weight.get(index) - 1.0;
+                Some(*total)
+            })
+            .collect();
+        Offsets { lines, extra }
+    }
+
+    fn get(&self, position: usize) -> f64 {
+        match self.lines.partition_point(|&line| line < position) {
+            0 => 0.0,
+            count => self.extra[count - 1],
+        }
+    }
+}
+
+/// Sums the difference between every pair of values
+fn sum_differences(mut values: Vec<usize>) -> usize {
+    values.sort_unstable();
+    let mut total = 0;
+    let mut prefix = 0;
+    for (i, value) in values.into_iter().enumerate() {
+        total += value * i - prefix;
+        prefix += value;
+    }
+    total
+}
+
+fn sum_weighted_differences(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mut total = 0.0;
+    let mut prefix = 0.0;
+    for (i, value) in values.into_iter().enumerate() {
+        total += value * i as f64 - prefix;
+        prefix += value;
+    }
+    total
+}
+
 fn part_1(input: &str) -> Result<usize> {
     let map = input.parse::<Map>()?;
     Ok(map.get_sum_paths(2))
@@ -93,8 +251,34 @@ fn part_2(input: &str) -> Result<usize> {
     Ok(map.get_sum_paths(1000000))
 }
 
+/// Expands by the given row and column weights, e.g. `2 1,3.5,1` to double empty rows and widen
+/// only the second empty column
+fn explore(input: &str, rows: &str, cols: &str) -> Result<String> {
+    let map = input.parse::<Map>()?;
+    let expansion = Expansion {
+        rows: rows.parse()?,
+        cols: cols.parse()?,
+    };
+    Ok(format!(
+        "Sum: {}\nNearest: {:?}\nFarthest: {:?}\n",
+        map.get_sum_paths_with(&expansion),
+        map.get_nearest_pair(&expansion),
+        map.get_farthest_pair(&expansion)
+    ))
+}
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
+
+    if let [_, rows, cols] = &args[..] {
+        match explore(input, rows, cols) {
+            Ok(output) => print!("{output}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
     println!("Part 1: {:?}", part_1(input));
     println!("Part 2: {:?}", part_2(input));
 }
@@ -103,6 +287,14 @@ fn main() {
 mod tests {
     use super::*;
 
+    /// Widens every empty row and column the same
+    fn uniform(factor: f64) -> Expansion {
+        Expansion {
+            rows: Weight::Uniform(factor),
+            cols: Weight::Uniform(factor),
+        }
+    }
+
     #[test]
     fn test_example() {
         let example = include_str!("example.txt");
@@ -136,4 +328,99 @@ mod tests {
 
         assert_eq!(expected, result)
     }
+
+    #[test]
+    fn test_large_factor() {
+        let map = include_str!("example.txt").parse::<Map>().unwrap();
+        for factor in [2, 10, 100] {
+            let sum = map.get_sum_paths_with(&uniform(factor as f64));
+            assert_eq!(map.get_sum_paths(factor) as f64, sum);
+        }
+
+        // Too wide to add up exactly as floats, but each extra width still adds 82
+        let factor = (1 << 53) + 1;
+        assert_eq!(292 + 82 * (factor - 1), map.get_sum_paths(factor));
+    }
+
+    /// Checks every pair of galaxies the slow way
+    fn get_pairs(map: &Map, expansion: &Expansion) -> Vec<f64> {
+        let positions = map.get_expanded_positions(expansion);
+        let mut distances = Vec::new();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[..i] {
+                distances.push((a.0 - b.0).abs() + (a.1 - b.1).abs());
+            }
+        }
+        distances
+    }
+
+    #[test]
+    fn test_separate_factors() {
+        let map = include_str!("example.txt").parse::<Map>().unwrap();
+        let expansion = Expansion {
+            rows: Weight::Uniform(2.0),
+            cols: Weight::Uniform(10.0),
+        };
+        let expected: f64 = get_pairs(&map, &expansion).iter().sum();
+        assert_eq!(expected, map.get_sum_paths_with(&expansion));
+
+        // Expanding only one way lands between the two uniform expansions
+        let sum = map.get_sum_paths_with(&expansion);
+        assert!(374.0 < sum && sum < 1030.0);
+    }
+
+    #[test]
+    fn test_weights() {
+        let map = include_str!("example.txt").parse::<Map>().unwrap();
+        assert_eq!(
+            map.get_sum_paths_with(&uniform(2.0)),
+            map.get_sum_paths_with(&Expansion {
+                rows: Weight::PerLine(vec![2.0, 2.0]),
+                cols: Weight::PerLine(vec![2.0, 2.0, 2.0]),
+            })
+        );
+
+        // Half a line wider is halfway between not expanding and doubling
+        let half = map.get_sum_paths_with(&uniform(1.5));
+        let none = map.get_sum_paths_with(&uniform(1.0));
+        assert_eq!((none + 374.0) / 2.0, half);
+
+        let expansion = Expansion {
+            rows: Weight::PerLine(vec![0.5, 3.0]),
+            cols: Weight::PerLine(vec![4.0]),
+        };
+        let expected: f64 = get_pairs(&map, &expansion).iter().sum();
+        assert_eq!(expected, map.get_sum_paths_with(&expansion));
+    }
+
+    #[test]
+    fn test_explore() {
+        let example = include_str!("example.txt");
+        assert!(explore(example, "2", "2")
+            .unwrap()
+            .starts_with("Sum: 374\n"));
+        assert!(explore(example, "10,10", "10,10,10")
+            .unwrap()
+            .starts_with("Sum: 1030\n"));
+        assert!(explore(example, "2", "x").is_err());
+    }
+
+    #[test]
+    fn test_nearest_farthest() {
+        let map = include_str!("example.txt").parse::<Map>().unwrap();
+        for expansion in [uniform(2.0), uniform(100.0)] {
+            let distances = get_pairs(&map, &expansion);
+            let (_, _, nearest) = map.get_nearest_pair(&expansion).unwrap();
+            let (_, _, farthest) = map.get_farthest_pair(&expansion).unwrap();
+            assert_eq!(distances.iter().copied().fold(f64::MAX, f64::min), nearest);
+            assert_eq!(distances.iter().copied().fold(0.0, f64::max), farthest);
+        }
+
+        let (_, _, distance) = map.get_nearest_pair(&uniform(2.0)).unwrap();
+        assert_eq!(5.0, distance);
+        assert!(Map::from_str("#")
+            .unwrap()
+            .get_nearest_pair(&uniform(2.0))
+            .is_none());
+    }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, env, str::FromStr};
 
 use anyhow::{anyhow, bail, Error, Result};
 
@@ -42,12 +42,7 @@ impl ContiguousGroups {
             return 0;
         }
 
-        let total_groups = self.0.len();
-        let taken_up_by_other_gaps = if total_groups > 2 {
-            total_groups - 2
-        } else {
-            0
-        };
+        let taken_up_by_other_gaps = self.0.len().saturating_sub(2);
 
         pattern_len - (total_damaged - taken_up_by_other_gaps)
     }
@@ -196,6 +191,51 @@ impl Row {
         valid_patterns
     }
 
+    /// Lazily lists every arrangement of this row as a complete pattern with no ? left, smallest
+    /// gaps first. The counts are used to skip over anything that can't lead to an arrangement.
+    fn arrangements(&self) -> Arrangements {
+        let mut cache = Cache::new();
+        let remaining = self.count_arrangements_with_cache(&mut cache);
+        Arrangements {
+            stack: vec![(String::new(), self.clone())],
+            cache,
+            remaining,
+        }
+    }
+
+    /// Finds the unknown springs that are the same in every arrangement, as their index and
+    /// whether they're operational (.) or damaged (#). Rows with no arrangements have none.
+    fn get_forced(&self) -> Vec<(usize, char)> {
+        let mut cache = Cache::new();
+        if self.count_arrangements_with_cache(&mut cache) == 0 {
+            return Vec::new();
+        }
+
+        let with = |index: usize, c: char| {
+            let mut pattern = self.pattern.0.clone();
+            pattern.replace_range(index..index + 1, &c.to_string());
+            Row {
+                pattern: Pattern(pattern),
+                contiguous_groups: self.contiguous_groups.clone(),
+            }
+        };
+
+        self.pattern
+            .0
+            .char_indices()
+            .filter(|&(_, c)| c == '?')
+            .filter_map(|(index, _)| {
+                let operational = with(index, '.').count_arrangements_with_cache(&mut cache);
+                let damaged = with(index, '#').count_arrangements_with_cache(&mut cache);
+                match (operational, damaged) {
+                    (0, _) => Some((index, '#')),
+                    (_, 0) => Some((index, '.')),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
     /// Expands this row as described in the problem. Copies contiguous groups and patterns separated by ?
     fn expand(&mut self, factor: usize) {
         self.contiguous_groups = ContiguousGroups(self.contiguous_groups.0.repeat(factor));
@@ -208,6 +248,102 @@ impl Row {
     }
 }
 
+/// Iterator over the arrangements of a [`Row`], see [`Row::arrangements`]
+struct Arrangements {
+    /// Patterns decided so far, with the rest of the row still to be worked out
+    stack: Vec<(String, Row)>,
+    cache: Cache,
+    remaining: usize,
+}
+
+impl Iterator for Arrangements {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((prefix, row)) = self.stack.pop() {
+            let Row {
+                pattern,
+                contiguous_groups,
+            } = &row;
+
+            // Once every group is placed, anything left over is operational
+            if contiguous_groups.0.is_empty() {
+                if pattern.0.contains('#') {
+                    continue;
+                }
+                self.remaining -= 1;
+                return Some(prefix + &".".repeat(pattern.len()));
+            }
+
+            // Same as counting, but pushed in reverse so the smallest gaps come out first
+            let largest_gap = contiguous_groups.get_largest_gap(pattern.len());
+            for gap_len in (0..=largest_gap).rev() {
+                let mut remaining_groups = contiguous_groups.clone();
+                let first_num = remaining_groups.pop_first();
+                let potential_match =
+                    Pattern::build_section(gap_len, first_num, remaining_groups.0.is_empty());
+                let (sub_pattern, next_pattern) = pattern.split_at(potential_match.len());
+
+                if sub_pattern.matches(&potential_match) {
+                    let sub_row = Row {
+                        pattern: next_pattern,
+                        contiguous_groups: remaining_groups,
+                    };
+                    if sub_row.count_arrangements_with_cache(&mut self.cache) > 0 {
+                        self.stack
+                            .push((prefix.clone() + &potential_match.0, sub_row));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Arrangements {}
+
+/// Finds the rows that have no possible arrangements, by line number starting from 1
+fn find_impossible_rows(input: &str) -> Result<Vec<usize>> {
+    let mut cache = Cache::new();
+    let mut impossible = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let row = line
+            .parse::<Row>()
+            .map_err(|e| anyhow!("Line {}: {e}", index + 1))?;
+        if row.count_arrangements_with_cache(&mut cache) == 0 {
+            impossible.push(index + 1);
+        }
+    }
+    Ok(impossible)
+}
+
+/// Shows every arrangement of the row on the given line, with the springs they all agree on
+fn show(input: &str, line_number: usize) -> Result<String> {
+    let row = line_number
+        .checked_sub(1)
+        .and_then(|index| input.lines().nth(index))
+        .ok_or(anyhow!("No line {line_number}"))?
+        .parse::<Row>()?;
+
+    let mut forced = row.pattern.0.clone();
+    for (index, c) in row.get_forced() {
+        forced.replace_range(index..index + 1, &c.to_string());
+    }
+
+    let arrangements = row.arrangements();
+    let mut output = format!("{} arrangements, forced: {forced}\n", arrangements.len());
+    for arrangement in arrangements {
+        output += &arrangement;
+        output += "\n";
+    }
+    Ok(output)
+}
+
 fn part_1(input: &str) -> Result<usize> {
     let mut cache = Cache::new();
     input.lines().try_fold(0, |acc, l| {
@@ -227,7 +363,29 @@ fn part_2(input: &str) -> Result<usize> {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
+
+    if let Some(line_number) = args.get(1).and_then(|arg| arg.parse::<usize>().ok()) {
+        match show(input, line_number) {
+            Ok(output) => print!("{output}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "impossible") {
+        match find_impossible_rows(input) {
+            Ok(lines) => {
+                for line in lines {
+                    println!("Line {line} has no possible arrangements");
+                }
+            }
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
     println!("Part 1: {:?}", part_1(input));
     println!("Part 2: {:?}", part_2(input));
 }
@@ -318,6 +476,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_arrangements() {
+        let row = "?###???????? 3,2,1".parse::<Row>().unwrap();
+        let arrangements: Vec<String> = row.arrangements().collect();
+        assert_eq!(10, arrangements.len());
+        assert_eq!(".###.##.#...", arrangements[0]);
+        assert_eq!(".###....##.#", arrangements[9]);
+        for arrangement in &arrangements {
+            let resolved = Row {
+                pattern: Pattern(arrangement.clone()),
+                contiguous_groups: row.contiguous_groups.clone(),
+            };
+            assert!(!arrangement.contains('?'));
+            assert!(resolved.pattern.matches(&row.pattern));
+            assert_eq!(1, resolved.count_arrangements_with_cache(&mut Cache::new()));
+        }
+
+        // The iterator knows how many are left without having to walk through them
+        let mut cache = Cache::new();
+        for line in include_str!("example.txt").lines() {
+            let mut row = line.parse::<Row>().unwrap();
+            row.expand(2);
+            let count = row.count_arrangements_with_cache(&mut cache);
+            let mut arrangements = row.arrangements();
+            assert_eq!(count, arrangements.len());
+            arrangements.next();
+            assert_eq!(count - 1, arrangements.len());
+            assert_eq!(count - 1, arrangements.count());
+        }
+    }
+
+    #[test]
+    fn test_get_forced() {
+        let row = "???.### 1,1,3".parse::<Row>().unwrap();
+        assert_eq!(vec![(0, '#'), (1, '.'), (2, '#')], row.get_forced());
+
+        let row = "?###???????? 3,2,1".parse::<Row>().unwrap();
+        assert_eq!(vec![(0, '.'), (4, '.')], row.get_forced());
+
+        let row = "?.? 1".parse::<Row>().unwrap();
+        assert!(row.get_forced().is_empty());
+
+        let row = "#.# 1".parse::<Row>().unwrap();
+        assert!(row.get_forced().is_empty());
+    }
+
+    #[test]
+    fn test_find_impossible_rows() {
+        let input = "???.### 1,1,3\n#.# 1\n?.? 1\n### 2";
+        assert_eq!(vec![2, 4], find_impossible_rows(input).unwrap());
+        assert!(find_impossible_rows("???.### 1,x").is_err());
+        assert!(find_impossible_rows(include_str!("example.txt"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_show() {
+        let expected = "\
+1 arrangements, forced: #.#.###
+#.#.###
+";
+        assert_eq!(expected, show(include_str!("example.txt"), 1).unwrap());
+        assert!(show(include_str!("example.txt"), 0).is_err());
+        assert!(show(include_str!("example.txt"), 7).is_err());
+    }
+
     #[test]
     fn test_build_pattern() {
         assert_eq!(".##.", Pattern::build_section(1, 2, false).0);
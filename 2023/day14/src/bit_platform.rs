@@ -0,0 +1,294 @@
+use std::{convert::Infallible, fmt::Display, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+
+use crate::{cycle, cycle::Cycle, Direction, Platform, Tile};
+
+/// The same platform as [`Platform`], but with each row and column packed into the bits of a
+/// word. Cube rocks never move, so the stretches between them are worked out up front and a
+/// tilt only needs to count the round rocks in each stretch and pack them to one end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitPlatform {
+    rows: usize,
+    cols: usize,
+    /// Round rocks in each row, with bit `c` set for column `c`
+    round_rows: Vec<u128>,
+    /// Round rocks in each column, with bit `r` set for row `r`
+    round_cols: Vec<u128>,
+    /// Cube rocks in each row, with bit `c` set for column `c`
+    cube_rows: Vec<u128>,
+    /// The stretches of each row between cube rocks, as the first bit and a mask
+    row_stretches: Vec<Vec<(u32, u128)>>,
+    /// The stretches of each column between cube rocks, as the first bit and a mask
+    col_stretches: Vec<Vec<(u32, u128)>>,
+}
+
+impl TryFrom<&Platform> for BitPlatform {
+    type Error = Error;
+
+    fn try_from(platform: &Platform) -> Result<Self> {
+        let rows = platform.grid.len();
+        let cols = platform.grid.first().map_or(0, |row| row.len());
+        if rows > u128::BITS as usize || cols > u128::BITS as usize {
+            bail!("Platform is {rows}x{cols}, but can't be over 128 in either direction");
+        }
+        if platform.grid.iter().any(|row| row.len() != cols) {
+            bail!("Platform rows are different lengths");
+        }
+
+        let mut round_rows = vec![0; rows];
+        let mut round_cols = vec![0; cols];
+        let mut cube_rows = vec![0; rows];
+        let mut cube_cols = vec![0; cols];
+        for (r, row) in platform.grid.iter().enumerate() {
+            for (c, tile) in row.iter().enumerate() {
+                match tile {
+                    Tile::RoundRock => {
+                        round_rows[r] |= 1 << c;
+                        round_cols[c] |= 1 << r;
+                    }
+                    Tile::CubeRock => {
+                        cube_rows[r] |= 1 << c;
+                        cube_cols[c] |= 1 << r;
+                    }
+                    Tile::Empty => {}
+                }
+            }
+        }
+
+        Ok(BitPlatform {
+            rows,
+            cols,
+            round_rows,
+            round_cols,
+            row_stretches: cube_rows.iter().map(|&c| get_stretches(c, cols)).collect(),
+            col_stretches: cube_cols.iter().map(|&c| get_stretches(c, rows)).collect(),
+            cube_rows,
+        })
+    }
+}
+
+impl FromStr for BitPlatform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        BitPlatform::try_from(&s.parse::<Platform>()?)
+    }
+}
+
+impl Display for BitPlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            (0..self.rows)
+                .map(|r| (0..self.cols)
+                    .map(|c| {
+                        let tile = if self.round_rows[r] & (1 << c) != 0 {
+                            Tile::RoundRock
+                        } else if self.cube_rows[r] & (1 << c) != 0 {
+                            Tile::CubeRock
+                        } else {
+                            Tile::Empty
+                        };
+                        char::from(tile)
+                    })
+                    .collect())
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
+    }
+}
+
+impl BitPlatform {
+    pub fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::North => {
+                pack(&mut self.round_cols, &self.col_stretches, false);
+                transpose(&self.round_cols, &mut self.round_rows);
+            }
+            Direction::South => {
+                pack(&mut self.round_cols, &self.col_stretches, true);
+                transpose(&self.round_cols, &mut self.round_rows);
+            }
+            Direction::West => {
+                pack(&mut self.round_rows, &self.row_stretches, false);
+                transpose(&self.round_rows, &mut self.round_cols);
+            }
+            Direction::East => {
+                pack(&mut self.round_rows, &self.row_stretches, true);
+                transpose(&self.round_rows, &mut self.round_cols);
+            }
+        }
+    }
+
+    pub fn spin(&mut self) {
+        self.tilt(Direction::North);
+        self.tilt(Direction::West);
+        self.tilt(Direction::South);
+        self.tilt(Direction::East);
+    }
+
+    /// Spins `count` times, skipping ahead once the platform starts repeating itself. Only the
+    /// round rocks move, so their rows are all that's needed to tell two platforms apart.
+    pub fn spin_times(&mut self, count: usize) -> Option<Cycle> {
+        let key = |platform: &BitPlatform| platform.round_rows.clone();
+        let Ok((platform, cycle)) = cycle::run_by_key(self.clone(), count, key, |platform| {
+            platform.spin();
+            Ok::<(), Infallible>(())
+        });
+        *self = platform;
+        cycle
+    }
+
+    pub fn calculate_load_north(&self) -> usize {
+        self.round_rows
+            .iter()
+            .enumerate()
+            .map(|(r, row)| (self.rows - r) * row.count_ones() as usize)
+            .sum()
+    }
+}
+
+// Private
+
+/// Splits a line of `len` tiles into the stretches between cube rocks
+fn get_stretches(cubes: u128, len: usize) -> Vec<(u32, u128)> {
+    let mut stretches = Vec::new();
+    let mut start = 0;
+
+    for i in 0..=len {
+        if i == len || cubes & (1 << i) != 0 {
+            if i > start {
+                let mask = (u128::MAX >> (u128::BITS as usize - (i - start))) << start;
+                stretches.push((start as u32, mask));
+            }
+            start = i + 1;
+        }
+    }
+
+    stretches
+}
+
+/// Moves every round rock in each line as far as it goes, to the high bits when `to_end` is set
+fn pack(lines: &mut [u128], stretches: &[Vec<(u32, u128)>], to_end: bool) {
+    for (line, stretches) in lines.iter_mut().zip(stretches) {
+        let mut packed = 0;
+        for &(start, mask) in stretches {
+            let count = (*line & mask).count_ones();
+            if count == 0 {
+                continue;
+            }
+
+            let rocks = u128::MAX >> (u128::BITS - count);
+            packed |= if to_end {
+                let end = u128::BITS - mask.leading_zeros();
+                rocks << (end - count)
+            } else {
+                rocks << start
+            };
+        }
+        *line = packed;
+    }
+}
+
+/// Rebuilds the other direction's lines from the set bits of these ones
+fn transpose(lines: &[u128], other: &mut [u128]) {
+    other.iter_mut().for_each(|line| *line = 0);
+
+    for (i, &line) in lines.iter().enumerate() {
+        let mut bits = line;
+        while bits != 0 {
+            let j = bits.trailing_zeros() as usize;
+            other[j] |= 1 << i;
+            bits &= bits - 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator so the random platforms are the same every run
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn platform(&mut self) -> String {
+            let rows = 1 + self.next() as usize % 12;
+            let cols = 1 + self.next() as usize % 12;
+            (0..rows)
+                .map(|_| {
+                    (0..cols)
+                        .map(|_| match self.next() % 4 {
+                            0 => 'O',
+                            1 => '#',
+                            _ => '.',
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        let input = include_str!("example.txt");
+        let mut platform: BitPlatform = input.parse().unwrap();
+        assert_eq!(platform.to_string(), input.trim_end());
+
+        platform.tilt(Direction::North);
+        assert_eq!(
+            platform.to_string(),
+            include_str!("example_rolled_north.txt").trim_end()
+        );
+        assert_eq!(platform.calculate_load_north(), 136);
+
+        let mut platform: BitPlatform = input.parse().unwrap();
+        platform.spin_times(1000000000);
+        assert_eq!(platform.calculate_load_north(), 64);
+    }
+
+    #[test]
+    fn test_matches_platform() {
+        let mut random = Random(0x2023_1214);
+        let directions = [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ];
+
+        for _ in 0..200 {
+            let input = random.platform();
+            let mut platform: Platform = input.parse().unwrap();
+            let mut bit_platform: BitPlatform = input.parse().unwrap();
+
+            for _ in 0..8 {
+                let direction = directions[random.next() as usize % 4];
+                platform.tilt(direction).unwrap();
+                bit_platform.tilt(direction);
+
+                assert_eq!(platform.to_string(), bit_platform.to_string(), "\n{input}");
+                assert_eq!(
+                    platform.calculate_load_north(),
+                    bit_platform.calculate_load_north()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_too_wide() {
+        let input = ".".repeat(129);
+        assert!(input.parse::<BitPlatform>().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a repeating simulation loops, counted in steps from the initial state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first step that's part of the loop
+    pub start: usize,
+    /// How many steps it takes to come back around
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step that ends up in the same state as `step`
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+}
+
+/// Runs `step` `count` times, remembering the key for every state so whole cycles can be
+/// skipped once one repeats. Returns the final state and the cycle, if one was found along the
+/// way. Pass `S::clone` as the key to remember every state whole.
+pub fn run_by_key<S, K, E>(
+    mut state: S,
+    count: usize,
+    key: impl Fn(&S) -> K,
+    mut step: impl FnMut(&mut S) -> Result<(), E>,
+) -> Result<(S, Option<Cycle>), E>
+where
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::from([(key(&state), 0)]);
+
+    for index in 1..=count {
+        step(&mut state)?;
+
+        if let Some(&start) = seen.get(&key(&state)) {
+            // Only the steps left over after the last full cycle need to be run
+            let cycle = Cycle {
+                start,
+                length: index - start,
+            };
+            for _ in 0..(count - index) % cycle.length {
+                step(&mut state)?;
+            }
+            return Ok((state, Some(cycle)));
+        }
+
+        seen.insert(key(&state), index);
+    }
+
+    Ok((state, None))
+}
+
+/// Same as [`run_by_key`], but uses Brent's algorithm so only a couple of states are kept at
+/// a time, at the cost of running some steps more than once.
+pub fn run_brent<S, K, E>(
+    state: S,
+    count: usize,
+    key: impl Fn(&S) -> K,
+    mut step: impl FnMut(&mut S) -> Result<(), E>,
+) -> Result<(S, Option<Cycle>), E>
+where
+    S: Clone,
+    K: Eq,
+{
+    if count == 0 {
+        return Ok((state, None));
+    }
+
+    // Find the cycle length with the hare racing ahead of a tortoise that teleports to it
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = key(&state);
+    let mut hare = state.clone();
+    step(&mut hare)?;
+    let mut steps = 1;
+    while tortoise != key(&hare) {
+        if steps == count {
+            // Never looped within the steps asked for
+            return Ok((hare, None));
+        }
+
+        if power == length {
+            tortoise = key(&hare);
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare)?;
+        steps += 1;
+        length += 1;
+    }
+
+    // Find the start with the hare a cycle length ahead, stepping both until they meet
+    let mut tortoise = state.clone();
+    let mut hare = state;
+    for _ in 0..length {
+        step(&mut hare)?;
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        step(&mut tortoise)?;
+        step(&mut hare)?;
+        start += 1;
+    }
+
+    // The tortoise is at the start of the cycle, so only the leftover steps are needed
+    let cycle = Cycle { start, length };
+    for _ in start..cycle.equivalent_step(count) {
+        step(&mut tortoise)?;
+    }
+
+    Ok((tortoise, Some(cycle)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    fn next(value: &mut u32) -> Result<(), Infallible> {
+        *value = (*value * *value + 1) % 255;
+        Ok(())
+    }
+
+    fn naive(count: usize) -> u32 {
+        let mut value = 3;
+        for _ in 0..count {
+            next(&mut value).unwrap();
+        }
+        value
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for count in 0..60 {
+            let expected = naive(count);
+            assert_eq!(
+                run_by_key(3, count, u32::clone, next).unwrap().0,
+                expected,
+                "{count}"
+            );
+            assert_eq!(
+                run_brent(3, count, |&v| v, next).unwrap().0,
+                expected,
+                "{count}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        // 3, 10, 101, 2, 5, 26, 167, 95, 101, 2, ...
+        let expected = Cycle {
+            start: 2,
+            length: 6,
+        };
+        assert_eq!(
+            run_by_key(3, 1000, u32::clone, next).unwrap().1,
+            Some(expected)
+        );
+        assert_eq!(run_brent(3, 1000, |&v| v, next).unwrap().1, Some(expected));
+        assert_eq!(run_by_key(3, 5, u32::clone, next).unwrap().1, None);
+        assert_eq!(expected.equivalent_step(1000), 2 + 998 % 6);
+    }
+}
@@ -1,7 +1,13 @@
-use std::{collections::HashMap, fmt::Display, str::FromStr, time::Instant};
+use std::{fmt::Display, str::FromStr, time::Instant};
 
 use anyhow::{anyhow, bail, Error, Result};
 
+use bit_platform::BitPlatform;
+use cycle::Cycle;
+
+mod bit_platform;
+mod cycle;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     North,
@@ -156,6 +162,15 @@ impl Platform {
         Ok(())
     }
 
+    /// Spins `count` times, skipping ahead once the platform starts repeating itself. Brent's
+    /// algorithm only keeps a couple of platforms around to compare.
+    fn spin_times(&mut self, count: usize) -> Result<Option<Cycle>> {
+        let (platform, cycle) =
+            cycle::run_brent(self.clone(), count, Platform::clone, Platform::spin)?;
+        *self = platform;
+        Ok(cycle)
+    }
+
     fn move_rock(&mut self, from: Coord, to: Coord) -> Result<()> {
         if self
             .grid
@@ -248,22 +263,16 @@ fn part_1(input: &str) -> Result<usize> {
 }
 
 fn part_2(input: &str) -> Result<usize> {
-    const SPINS: i32 = 1000000000;
-    let mut prev_platforms = HashMap::new();
+    const SPINS: usize = 1000000000;
     let mut platform = input.parse::<Platform>()?;
-    prev_platforms.insert(platform.clone(), 0);
-    for index in 1..=SPINS {
-        platform.spin()?;
-        if let Some(prev_index) = prev_platforms.get(&platform) {
-            // Cycle is happening, find what would be the final cycle
-            let end_i = prev_index + (SPINS - prev_index) % (index - prev_index);
-            if let Some((end_platform, _)) = prev_platforms.iter().find(|&(_, &i)| i == end_i) {
-                platform = end_platform.clone();
-                break;
-            }
-        }
-        prev_platforms.insert(platform.clone(), index);
+
+    // Packing rocks into bits is much faster, but only fits platforms up to 128 across
+    if let Ok(mut bit_platform) = BitPlatform::try_from(&platform) {
+        bit_platform.spin_times(SPINS);
+        return Ok(bit_platform.calculate_load_north());
     }
+
+    platform.spin_times(SPINS)?;
     Ok(platform.calculate_load_north())
 }
 
@@ -329,4 +338,38 @@ mod tests {
         platform.spin().unwrap();
         assert_eq!(expected3, platform);
     }
+
+    #[test]
+    fn test_spin_times() {
+        let platform = include_str!("example.txt").parse::<Platform>().unwrap();
+
+        let mut spun = platform.clone();
+        let cycle = spun.spin_times(1000000000).unwrap();
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 3,
+                length: 7
+            })
+        );
+        assert_eq!(spun.calculate_load_north(), 64);
+
+        // Remembering every platform whole lands on the same load
+        let (by_key, by_key_cycle) = cycle::run_by_key(
+            platform.clone(),
+            1000000000,
+            Platform::clone,
+            Platform::spin,
+        )
+        .unwrap();
+        assert_eq!(by_key_cycle, cycle);
+        assert_eq!(by_key, spun);
+
+        // As well as spinning the long way round to the equivalent spin
+        let mut slow = platform;
+        for _ in 0..cycle.unwrap().equivalent_step(1000000000) {
+            slow.spin().unwrap();
+        }
+        assert_eq!(slow.calculate_load_north(), 64);
+    }
 }
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fmt::Display,
+    str::FromStr,
+};
 
 use anyhow::{anyhow, bail, Error, Result};
 
@@ -39,25 +44,95 @@ struct Lens {
     focal_length: u8,
 }
 
-#[derive(Debug)]
+/// A box of lenses, kept in the order they were added. Removing a lens leaves a gap behind in
+/// its slot so nothing needs to shift, and the gaps are cleared out once there are enough of them.
+#[derive(Debug, Default)]
 struct Box {
-    lenses: Vec<Lens>,
+    slots: Vec<Option<Lens>>,
+    /// Which slot each label is in
+    index: HashMap<String, usize>,
 }
 
-#[derive(Debug)]
+impl Box {
+    fn add_lens(&mut self, lens: Lens) {
+        if let Some(&slot) = self.index.get(&lens.label) {
+            // Replace the lens in the same slot
+            self.slots[slot] = Some(lens);
+        } else {
+            // Add the lens to the end
+            self.index.insert(lens.label.clone(), self.slots.len());
+            self.slots.push(Some(lens));
+        }
+    }
+
+    fn remove_lens(&mut self, label: &str) {
+        if let Some(slot) = self.index.remove(label) {
+            self.slots[slot] = None;
+            if self.slots.len() > 2 * self.index.len() + 8 {
+                self.compact();
+            }
+        }
+    }
+
+    /// Drops the gaps left by removed lenses
+    fn compact(&mut self) {
+        self.slots.retain(|slot| slot.is_some());
+        for (slot, lens) in self.slots.iter().flatten().enumerate() {
+            if let Some(index) = self.index.get_mut(&lens.label) {
+                *index = slot;
+            }
+        }
+    }
+
+    fn lenses(&self) -> impl Iterator<Item = &Lens> {
+        self.slots.iter().flatten()
+    }
+}
+
+#[derive(Debug, Default)]
 struct BoxLine {
     boxes: HashMap<u8, Box>,
+    /// Every label seen so far in each box, in the order they first showed up
+    labels: HashMap<u8, Vec<String>>,
+    /// The same labels, to check quickly whether one has been seen. A label always hashes to
+    /// the same box, so one set covers them all.
+    seen_labels: HashSet<String>,
+}
+
+impl Display for BoxLine {
+    /// Lists the boxes with lenses in them, e.g. `Box 0: [rn 1] [cm 2]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut box_indices: Vec<&u8> = self.boxes.keys().collect();
+        box_indices.sort();
+
+        for box_index in box_indices {
+            let lenses: Vec<String> = self.boxes[box_index]
+                .lenses()
+                .map(|l| format!("[{} {}]", l.label, l.focal_length))
+                .collect();
+            if !lenses.is_empty() {
+                writeln!(f, "Box {box_index}: {}", lenses.join(" "))?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl BoxLine {
     fn process_sequence(s: &str) -> Result<Self> {
-        let mut box_line = BoxLine {
-            boxes: HashMap::new(),
-        };
+        BoxLine::replay(s, |_, _| {})
+    }
+
+    /// Same as [`BoxLine::process_sequence`], calling `on_step` with each step and the boxes
+    /// once it's been carried out.
+    fn replay(s: &str, mut on_step: impl FnMut(&str, &BoxLine)) -> Result<Self> {
+        let mut box_line = BoxLine::default();
 
         for step in strip_newlines(s).split(',') {
             let op = step.parse()?;
             box_line.execute(op);
+            on_step(step, &box_line);
         }
 
         Ok(box_line)
@@ -72,42 +147,44 @@ impl BoxLine {
 
     fn add_lens(&mut self, lens: Lens) {
         let box_index = hash(&lens.label);
-
-        // Check if box exists
-        if let Some(b) = self.boxes.get_mut(&box_index) {
-            // If so, check if there's already a lens with the same label
-            if let Some(lens_index) = b.lenses.iter().position(|l| l.label == lens.label) {
-                // Replace the lense at the given index
-                b.lenses[lens_index] = lens;
-            } else {
-                // Add the lense to the end
-                b.lenses.push(lens);
-            }
-        } else {
-            // Initialize a new box with the lens
-            self.boxes.insert(box_index, Box { lenses: vec![lens] });
-        }
+        self.see_label(box_index, &lens.label);
+        self.boxes.entry(box_index).or_default().add_lens(lens);
     }
 
     fn remove_lens(&mut self, label: String) {
         let box_index = hash(&label);
-
-        // Check if box exists
+        self.see_label(box_index, &label);
         if let Some(b) = self.boxes.get_mut(&box_index) {
-            // Look for the lens with the same label
-            if let Some(lens_index) = b.lenses.iter().position(|l| l.label == label) {
-                // Remove it
-                b.lenses.remove(lens_index);
-            }
+            b.remove_lens(&label);
+        }
+    }
+
+    fn see_label(&mut self, box_index: u8, label: &str) {
+        if self.seen_labels.insert(label.to_string()) {
+            self.labels
+                .entry(box_index)
+                .or_default()
+                .push(label.to_string());
         }
     }
 
+    /// Lists the boxes that more than one label has hashed to, along with those labels
+    fn get_collisions(&self) -> Vec<(u8, &[String])> {
+        let mut collisions: Vec<(u8, &[String])> = self
+            .labels
+            .iter()
+            .filter(|(_, labels)| labels.len() > 1)
+            .map(|(&box_index, labels)| (box_index, &labels[..]))
+            .collect();
+        collisions.sort();
+        collisions
+    }
+
     fn get_total_focusing_power(&self) -> usize {
         self.boxes
             .iter()
             .map(|(&b_i, b)| {
-                b.lenses
-                    .iter()
+                b.lenses()
                     .enumerate()
                     .map(|(l_i, l)| (b_i as usize + 1) * (l_i + 1) * l.focal_length as usize)
                     .sum::<usize>()
@@ -143,8 +220,30 @@ fn part_2(input: &str) -> Result<usize> {
     Ok(box_line.get_total_focusing_power())
 }
 
+fn replay(input: &str) -> Result<String> {
+    let mut output = String::new();
+    let box_line = BoxLine::replay(input, |step, box_line| {
+        output += &format!("After \"{step}\":\n{box_line}\n");
+    })?;
+
+    for (box_index, labels) in box_line.get_collisions() {
+        output += &format!("Box {box_index} collisions: {}\n", labels.join(", "));
+    }
+    Ok(output)
+}
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
+
+    if args.get(1).is_some_and(|arg| arg == "replay") {
+        match replay(input) {
+            Ok(output) => print!("{output}"),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
     println!("Part 1: {}", get_verification_number(input));
     println!("Part 2: {:?}", part_2(input));
 }
@@ -171,4 +270,72 @@ mod tests {
         let result = part_2(input);
         assert_eq!(145, result.unwrap());
     }
+
+    #[test]
+    fn test_replay() {
+        let input = include_str!("example.txt");
+        let output = replay(input).unwrap();
+        let expected_start = "\
+After \"rn=1\":
+Box 0: [rn 1]
+
+After \"cm-\":
+Box 0: [rn 1]
+
+After \"qp=3\":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+";
+        assert!(output.starts_with(expected_start));
+
+        let expected_end = "\
+After \"ot=7\":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+
+Box 0 collisions: rn, cm
+Box 3 collisions: pc, ot, ab
+";
+        assert!(output.ends_with(expected_end), "{output}");
+    }
+
+    #[test]
+    fn test_remove_many() {
+        let mut b = Box::default();
+        for i in 0..100u8 {
+            b.add_lens(Lens {
+                label: format!("l{i}"),
+                focal_length: i % 9 + 1,
+            });
+        }
+        for i in (0..100).filter(|i| i % 10 != 0) {
+            b.remove_lens(&format!("l{i}"));
+        }
+        b.add_lens(Lens {
+            label: "l50".to_string(),
+            focal_length: 9,
+        });
+
+        let lenses: Vec<(&str, u8)> = b
+            .lenses()
+            .map(|l| (l.label.as_str(), l.focal_length))
+            .collect();
+        assert_eq!(
+            vec![
+                ("l0", 1),
+                ("l10", 2),
+                ("l20", 3),
+                ("l30", 4),
+                ("l40", 5),
+                ("l50", 9),
+                ("l60", 7),
+                ("l70", 8),
+                ("l80", 9),
+                ("l90", 1),
+            ],
+            lenses
+        );
+        assert!(b.slots.len() < 100);
+    }
 }
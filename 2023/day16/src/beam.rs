@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Coord, Direction, Grid, TileType};
+
+/// Precomputed beam paths between splitters, so the energized tiles for any entry point can be
+/// found without walking the whole grid again.
+///
+/// A beam that hits the flat side of a splitter always sends out the same two beams no matter
+/// where it came from, so every such splitter becomes a node in a graph with edges to the
+/// splitters its beams run into next. Splitters that feed each other are collapsed into one
+/// component, and the energized tiles for each component are worked out once and shared.
+pub struct BeamEngine {
+    rows: usize,
+    cols: usize,
+    grid: Vec<Vec<TileType>>,
+    /// Which component each splitter belongs to
+    components: HashMap<Coord, usize>,
+    /// Every tile energized once a beam reaches each component
+    energized: Vec<Vec<u64>>,
+}
+
+impl BeamEngine {
+    pub fn new(grid: &Grid) -> Self {
+        let mut engine = BeamEngine {
+            rows: grid.tiles.len(),
+            cols: grid.tiles.first().map_or(0, |row| row.len()),
+            grid: grid
+                .tiles
+                .iter()
+                .map(|row| row.iter().map(|tile| tile.tile_type.clone()).collect())
+                .collect(),
+            components: HashMap::new(),
+            energized: Vec::new(),
+        };
+
+        // Follow both beams out of every splitter
+        let mut splitters: Vec<Coord> = Vec::new();
+        let mut tiles: Vec<Vec<u64>> = Vec::new();
+        let mut edges: Vec<Vec<Coord>> = Vec::new();
+        for (row, line) in engine.grid.iter().enumerate() {
+            for (col, tile_type) in line.iter().enumerate() {
+                let TileType::Splitter(c) = tile_type else {
+                    continue;
+                };
+
+                let coord = Coord { row, col };
+                let mut set = engine.empty_set();
+                engine.insert(&mut set, coord);
+                let mut next = Vec::new();
+
+                // Both directions that come out are the same however the splitter is hit
+                let directions = match c {
+                    '-' => [Direction::Left, Direction::Right],
+                    _ => [Direction::Up, Direction::Down],
+                };
+                for direction in directions {
+                    if let Some(start) = engine.step(coord, direction) {
+                        if let Some(splitter) = engine.trace(start, direction, &mut set) {
+                            next.push(splitter);
+                        }
+                    }
+                }
+
+                splitters.push(coord);
+                tiles.push(set);
+                edges.push(next);
+            }
+        }
+
+        let ids: HashMap<Coord, usize> = splitters
+            .iter()
+            .enumerate()
+            .map(|(id, &coord)| (coord, id))
+            .collect();
+        let edges: Vec<Vec<usize>> = edges
+            .into_iter()
+            .map(|next| next.iter().map(|coord| ids[coord]).collect())
+            .collect();
+
+        // Tarjan's algorithm hands out components with the ones downstream first, so each
+        // component's energized set only needs sets that have already been built
+        let component_of = strongly_connected_components(&edges);
+        let count = component_of.iter().max().map_or(0, |&max| max + 1);
+        let mut members = vec![Vec::new(); count];
+        for (id, &component) in component_of.iter().enumerate() {
+            members[component].push(id);
+        }
+
+        for (component, ids) in members.iter().enumerate() {
+            let mut set = engine.empty_set();
+            for &id in ids {
+                union(&mut set, &tiles[id]);
+                for &next in &edges[id] {
+                    if component_of[next] != component {
+                        union(&mut set, &engine.energized[component_of[next]]);
+                    }
+                }
+            }
+            engine.energized.push(set);
+        }
+
+        engine.components = splitters.into_iter().zip(component_of).collect();
+        engine
+    }
+
+    /// How many tiles end up energized from a beam entering `coord` heading in `direction`
+    pub fn energize_from(&self, coord: Coord, direction: Direction) -> usize {
+        let mut set = self.empty_set();
+        if coord.row < self.rows && coord.col < self.cols {
+            if let Some(splitter) = self.trace(coord, direction, &mut set) {
+                union(&mut set, &self.energized[self.components[&splitter]]);
+            }
+        }
+
+        set.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Follows a beam, marking tiles as energized until it hits the flat side of a splitter,
+    /// leaves the grid, or loops back on itself. Returns the splitter it hit, if any.
+    fn trace(&self, mut coord: Coord, mut direction: Direction, set: &mut [u64]) -> Option<Coord> {
+        let mut seen = HashSet::new();
+
+        while seen.insert((coord, direction)) {
+            self.insert(set, coord);
+
+            direction = match self.grid[coord.row][coord.col] {
+                TileType::Empty => direction,
+                TileType::Mirror(c) => direction.reflect(c),
+                TileType::Splitter(c) => {
+                    if direction.split(c).is_some() {
+                        return Some(coord);
+                    }
+                    direction
+                }
+            };
+
+            coord = self.step(coord, direction)?;
+        }
+
+        None
+    }
+
+    fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        let (dr, dc) = direction.get_delta();
+        let row = coord.row.checked_add_signed(dr)?;
+        let col = coord.col.checked_add_signed(dc)?;
+        if row < self.rows && col < self.cols {
+            Some(Coord { row, col })
+        } else {
+            None
+        }
+    }
+
+    fn empty_set(&self) -> Vec<u64> {
+        vec![0; (self.rows * self.cols).div_ceil(64)]
+    }
+
+    fn insert(&self, set: &mut [u64], coord: Coord) {
+        let index = coord.row * self.cols + coord.col;
+        set[index / 64] |= 1 << (index % 64);
+    }
+}
+
+// Private
+
+fn union(set: &mut [u64], other: &[u64]) {
+    for (word, other) in set.iter_mut().zip(other) {
+        *word |= other;
+    }
+}
+
+/// Tarjan's algorithm, returning the component of each node. Components are numbered in the
+/// order they're completed, so every edge leads to a component with the same or lower number.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<usize> {
+    struct State<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        component: Vec<usize>,
+        next_component: usize,
+    }
+
+    fn connect(state: &mut State, node: usize) {
+        state.index[node] = Some(state.next_index);
+        state.low_link[node] = state.next_index;
+        state.next_index += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+
+        for &next in &state.edges[node] {
+            match state.index[next] {
+                None => {
+                    connect(state, next);
+                    state.low_link[node] = state.low_link[node].min(state.low_link[next]);
+                }
+                Some(index) if state.on_stack[next] => {
+                    state.low_link[node] = state.low_link[node].min(index);
+                }
+                _ => {}
+            }
+        }
+
+        // This is the root of a component, so everything above it on the stack belongs to it
+        if Some(state.low_link[node]) == state.index[node] {
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                state.component[member] = state.next_component;
+                if member == node {
+                    break;
+                }
+            }
+            state.next_component += 1;
+        }
+    }
+
+    let count = edges.len();
+    let mut state = State {
+        edges,
+        index: vec![None; count],
+        low_link: vec![0; count],
+        on_stack: vec![false; count],
+        stack: Vec::new(),
+        next_index: 0,
+        component: vec![0; count],
+        next_component: 0,
+    };
+
+    for node in 0..count {
+        if state.index[node].is_none() {
+            connect(&mut state, node);
+        }
+    }
+
+    state.component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // 0 -> 1 -> 2 -> 0 form a loop that feeds 3, and 4 stands alone
+        let edges = vec![vec![1], vec![2], vec![0, 3], vec![], vec![]];
+        let components = strongly_connected_components(&edges);
+
+        assert_eq!(components[0], components[1]);
+        assert_eq!(components[1], components[2]);
+        assert_ne!(components[2], components[3]);
+        assert!(components[3] < components[0]);
+        assert_ne!(components[4], components[0]);
+    }
+
+    #[test]
+    fn test_energize_from_matches_grid() {
+        let grid: Grid = include_str!("example.txt").parse().unwrap();
+        let engine = BeamEngine::new(&grid);
+        let rows = grid.tiles.len();
+        let cols = grid.tiles[0].len();
+
+        let mut starts = Vec::new();
+        for row in 0..rows {
+            starts.push((Coord { row, col: 0 }, Direction::Right));
+            starts.push((Coord { row, col: cols - 1 }, Direction::Left));
+        }
+        for col in 0..cols {
+            starts.push((Coord { row: 0, col }, Direction::Down));
+            starts.push((Coord { row: rows - 1, col }, Direction::Up));
+        }
+
+        for (coord, direction) in starts {
+            let mut expected = grid.clone();
+            expected.energize((coord, direction));
+            assert_eq!(
+                engine.energize_from(coord, direction),
+                expected.count_energized(),
+                "{:?} {:?}",
+                coord,
+                direction
+            );
+        }
+    }
+}
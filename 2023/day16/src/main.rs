@@ -1,7 +1,11 @@
-use std::{cmp::max, collections::HashSet, str::FromStr};
+use std::{collections::HashSet, str::FromStr, thread};
 
 use anyhow::{bail, Error, Ok, Result};
 
+use beam::BeamEngine;
+
+mod beam;
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 enum Direction {
     Up,
@@ -81,7 +85,7 @@ impl Tile {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Coord {
     row: usize,
     col: usize,
@@ -175,53 +179,63 @@ fn part_1(input: &str) -> Result<usize> {
 }
 
 fn part_2(input: &str) -> Result<usize> {
-    let original_grid: Grid = input.parse()?;
-    let mut highest_energy = 0;
-    let row_len = original_grid.tiles.len();
+    let grid: Grid = input.parse()?;
+    let row_len = grid.tiles.len();
     if row_len == 0 {
         bail!("No rows");
     }
-    let col_len = original_grid.tiles[0].len();
+    let col_len = grid.tiles[0].len();
     if col_len == 0 {
         bail!("No columns");
     }
 
+    let mut starts = Vec::new();
     for row in 0..row_len {
-        // Right
-        let mut grid = original_grid.clone();
-        grid.energize((Coord { row, col: 0 }, Direction::Right));
-        highest_energy = max(highest_energy, grid.count_energized());
-
-        // Left
-        let mut grid = original_grid.clone();
-        grid.energize((
+        starts.push((Coord { row, col: 0 }, Direction::Right));
+        starts.push((
             Coord {
                 row,
                 col: col_len - 1,
             },
             Direction::Left,
         ));
-        highest_energy = max(highest_energy, grid.count_energized());
     }
-
     for col in 0..col_len {
-        // Down
-        let mut grid = original_grid.clone();
-        grid.energize((Coord { row: 0, col }, Direction::Down));
-        highest_energy = max(highest_energy, grid.count_energized());
-
-        // Up
-        let mut grid = original_grid.clone();
-        grid.energize((
+        starts.push((Coord { row: 0, col }, Direction::Down));
+        starts.push((
             Coord {
                 row: row_len - 1,
                 col,
             },
             Direction::Up,
         ));
-        highest_energy = max(highest_energy, grid.count_energized());
     }
 
+    // Split the edges between threads, they only need to read from the engine
+    let engine = BeamEngine::new(&grid);
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = starts.len().div_ceil(threads);
+    let highest_energy = thread::scope(|scope| {
+        let handles: Vec<_> = starts
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let engine = &engine;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&(coord, direction)| engine.energize_from(coord, direction))
+                        .max()
+                        .unwrap_or_default()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .max()
+            .unwrap_or_default()
+    });
+
     Ok(highest_energy)
 }
 
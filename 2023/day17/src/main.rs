@@ -1,66 +1,145 @@
 use std::env;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
 use map::Map;
-use path_finder::find_minimal_heatloss;
+use path_finder::{find_minimal_heatloss, find_minimal_heatloss_astar, render_path, Search};
+use rules::Rules;
 
 mod coord;
 mod map;
 mod path_finder;
+mod rules;
 
-fn part_1(input: &str) -> Result<u32> {
-    let map: Map = input.parse()?;
-    Ok(find_minimal_heatloss(&map, 0, 3))
+fn part_1(input: &str, search: Search) -> Result<(u32, String)> {
+    solve(input, &Rules::new(0, 3), search)
+}
+
+fn part_2(input: &str, search: Search) -> Result<(u32, String)> {
+    solve(input, &Rules::new(4, 10), search)
 }
 
-fn part_2(input: &str) -> Result<u32> {
+/// Finds the least heat loss, along with the map drawn with the path that gives it
+fn solve(input: &str, rules: &Rules, search: Search) -> Result<(u32, String)> {
     let map: Map = input.parse()?;
-    Ok(find_minimal_heatloss(&map, 4, 10))
+    let (path, heatloss) = search(&map, rules).ok_or(anyhow!("No path found"))?;
+    Ok((heatloss, render_path(&map, &path)))
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let input = include_str!("input.txt");
 
+    let search: Search = match args.get(2).map(|s| s.as_str()) {
+        None | Some("dijkstra") => find_minimal_heatloss,
+        Some("astar") => find_minimal_heatloss_astar,
+        Some(other) => {
+            println!("Unknown search {other}, expected dijkstra or astar");
+            return;
+        }
+    };
+
     let result = match args.get(1).map(|s| s.as_str()) {
-        Some("1") => part_1(input),
-        Some("2") => part_2(input),
-        _ => part_1(input),
+        Some("1") => part_1(input, search),
+        Some("2") => part_2(input, search),
+        _ => part_1(input, search),
     };
 
-    println!("{:?}", result);
+    if let Ok((_, path)) = &result {
+        print!("{}", path);
+    }
+    println!("{:?}", result.map(|(heatloss, _)| heatloss));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use path_finder::Position;
 
     #[test]
     fn test_part_1() {
         let input = include_str!("example.txt");
-        let result = part_1(input);
-        assert_eq!(102, result.unwrap());
+        let result = part_1(input, find_minimal_heatloss);
+        assert_eq!(102, result.unwrap().0);
     }
 
     #[test]
     fn test_part_2_1() {
         let input = include_str!("example.txt");
-        let result = part_2(input);
-        assert_eq!(94, result.unwrap());
+        let result = part_2(input, find_minimal_heatloss);
+        assert_eq!(94, result.unwrap().0);
     }
 
     #[test]
     fn test_part_2_2() {
         let input = include_str!("example2.txt");
-        let result = part_2(input);
-        assert_eq!(71, result.unwrap());
+        let result = part_2(input, find_minimal_heatloss);
+        assert_eq!(71, result.unwrap().0);
     }
 
     #[test]
     fn test_part_2_3() {
         let input = include_str!("example3.txt");
-        let result = part_2(input);
-        assert_eq!(34, result.unwrap());
+        let result = part_2(input, find_minimal_heatloss);
+        assert_eq!(34, result.unwrap().0);
+    }
+
+    /// Checks the path goes from the start to the end one block at a time within the rules, and
+    /// loses the heat it says it does
+    fn assert_valid_path(map: &Map, rules: &Rules, path: &[Position], heatloss: u32) {
+        assert_eq!(rules.get_start(), path[0].location);
+        assert_eq!(rules.get_end(map), path[path.len() - 1].location);
+        for pair in path.windows(2) {
+            let (from, to) = (&pair[0].location, &pair[1].location);
+            assert_eq!(to.x, from.x + pair[1].direction.x);
+            assert_eq!(to.y, from.y + pair[1].direction.y);
+            assert!(pair[1].straight_line_steps <= rules.max_steps);
+        }
+
+        let total: u32 = path
+            .iter()
+            .skip(1)
+            .map(|p| map.get_value(&p.location).unwrap() as u32)
+            .sum();
+        assert_eq!(heatloss, total);
+    }
+
+    #[test]
+    fn test_draw() {
+        let input = include_str!("example.txt");
+        let map: Map = input.parse().unwrap();
+        let rules = Rules::new(0, 3);
+
+        // The two searches can settle on different paths with the same heat loss
+        let searches: [Search; 2] = [find_minimal_heatloss, find_minimal_heatloss_astar];
+        for search in searches {
+            let (path, heatloss) = search(&map, &rules).unwrap();
+            assert_eq!(102, heatloss);
+            assert_valid_path(&map, &rules, &path, heatloss);
+
+            let drawing = render_path(&map, &path);
+            let drawn = drawing.chars().filter(|c| "<>^v".contains(*c)).count();
+            assert_eq!(path.len(), drawn);
+            assert_eq!(input.lines().count(), drawing.lines().count());
+            assert_eq!((102, drawing), part_1(input, search).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        for (input, min_steps, max_steps) in [
+            (include_str!("example.txt"), 0, 3),
+            (include_str!("example.txt"), 4, 10),
+            (include_str!("example2.txt"), 4, 10),
+            (include_str!("example3.txt"), 4, 10),
+        ] {
+            let map: Map = input.parse().unwrap();
+            let rules = Rules::new(min_steps, max_steps);
+            let (path, heatloss) = find_minimal_heatloss(&map, &rules).unwrap();
+            let (astar_path, astar_heatloss) = find_minimal_heatloss_astar(&map, &rules).unwrap();
+            assert_eq!(heatloss, astar_heatloss);
+            assert_valid_path(&map, &rules, &path, heatloss);
+            assert_valid_path(&map, &rules, &astar_path, astar_heatloss);
+        }
     }
 }
@@ -1,143 +1,151 @@
-use pathfinding::prelude::astar;
+use std::collections::HashMap;
+
+use pathfinding::prelude::{astar, dijkstra};
 
 use crate::coord::Coord;
 use crate::map::Map;
+use crate::rules::Rules;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Position {
-    direction: Coord,
-    location: Coord,
-    straight_line_steps: u32,
+pub struct Position {
+    pub direction: Coord,
+    pub location: Coord,
+    pub straight_line_steps: u32,
+    /// Only counted when the rules limit turns, to keep the search space small otherwise
+    pub turns: u32,
 }
 
-pub fn find_minimal_heatloss(map: &Map, min_steps: u32, max_steps: u32) -> u32 {
-    let start = Position {
-        direction: Coord { x: 1, y: 0 },
-        location: Coord { x: 0, y: 0 },
-        straight_line_steps: 0,
-    };
-    let size = map.get_size();
-    let end = Coord {
-        x: size.x - 1,
-        y: size.y - 1,
-    };
-
-    let result = astar(
+/// A way of finding the path with the least heat loss, and the heat lost along it
+pub type Search = fn(&Map, &Rules) -> Option<(Vec<Position>, u32)>;
+
+/// Finds the path with the least heat loss from the start to the end using Dijkstra
+pub fn find_minimal_heatloss(map: &Map, rules: &Rules) -> Option<(Vec<Position>, u32)> {
+    let start = get_start(rules);
+    let end = rules.get_end(map);
+
+    dijkstra(
         &start,
-        |p| get_valid_directions(map, p, min_steps, max_steps),
-        |p| map.distance(&p.location, &end),
-        |p| p.straight_line_steps >= min_steps && p.location == end,
+        |p| get_valid_directions(map, p, rules),
+        |p| p.straight_line_steps >= rules.min_steps && p.location == end,
     )
-    .unwrap();
+}
+
+/// Same as [`find_minimal_heatloss`], but guided by the distance left to the end. As long as
+/// entering a block loses at least 1 heat, the distance never overestimates and the path is
+/// still optimal.
+pub fn find_minimal_heatloss_astar(map: &Map, rules: &Rules) -> Option<(Vec<Position>, u32)> {
+    let start = get_start(rules);
+    let end = rules.get_end(map);
 
-    display_path(map, &result.0);
-    result.1
+    astar(
+        &start,
+        |p| get_valid_directions(map, p, rules),
+        |p| map.distance(&p.location, &end),
+        |p| p.straight_line_steps >= rules.min_steps && p.location == end,
+    )
 }
 
-fn display_path(map: &Map, path: &Vec<Position>) {
+/// Draws the map with the path's direction of travel in place of the visited cells
+pub fn render_path(map: &Map, path: &[Position]) -> String {
+    let mut visited = HashMap::new();
+    for position in path {
+        visited
+            .entry(position.location.clone())
+            .or_insert(&position.direction);
+    }
+
+    let mut output = String::new();
     for (y, row) in map.data.iter().enumerate() {
         for (x, value) in row.iter().enumerate() {
             let coord = Coord {
                 x: x as i32,
                 y: y as i32,
             };
-            let mut found = false;
-            for position in path {
-                if position.location == coord {
-                    let c = match position.direction {
-                        Coord { x: 1, y: 0 } => '>',
-                        Coord { x: -1, y: 0 } => '<',
-                        Coord { x: 0, y: 1 } => 'v',
-                        Coord { x: 0, y: -1 } => '^',
-                        _ => '?',
-                    };
-                    print!("{}", c);
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                print!("{}", value);
-            }
+            let c = match visited.get(&coord) {
+                Some(Coord { x: 1, y: 0 }) => '>',
+                Some(Coord { x: -1, y: 0 }) => '<',
+                Some(Coord { x: 0, y: 1 }) => 'v',
+                Some(Coord { x: 0, y: -1 }) => '^',
+                Some(_) => '?',
+                None => (b'0' + value) as char,
+            };
+            output.push(c);
         }
-        println!();
+        output.push('\n');
     }
+
+    output
 }
 
 // Private
 
-fn get_valid_directions(
-    map: &Map,
-    position: &Position,
-    min_steps: u32,
-    max_steps: u32,
-) -> Vec<(Position, u32)> {
-    let mut valid_directions = Vec::new();
-
+fn get_valid_directions(map: &Map, position: &Position, rules: &Rules) -> Vec<(Position, u32)> {
     let Position {
         direction,
         location,
         straight_line_steps,
+        turns,
     } = position;
 
-    // Check if moving straight is valid
-    let straight_location = Coord {
-        x: location.x + direction.x,
-        y: location.y + direction.y,
-    };
-    if straight_line_steps < &max_steps && map.is_within(&straight_location) {
-        let heatloss = map.get_value(&straight_location).unwrap() as u32;
-        valid_directions.push((
-            Position {
-                direction: direction.clone(),
-                location: straight_location,
-                straight_line_steps: straight_line_steps + 1,
-            },
-            heatloss,
-        ));
-    }
+    // Each candidate direction, and whether it's a turn
+    let mut moves = Vec::new();
+    if *straight_line_steps == 0 {
+        // Haven't moved yet, so any direction is fine
+        let mut start_direction = direction.clone();
+        for _ in 0..4 {
+            start_direction = turn_left(&start_direction);
+            moves.push((start_direction.clone(), false));
+        }
+    } else {
+        if straight_line_steps < &rules.max_steps {
+            moves.push((direction.clone(), false));
+        }
 
-    // Check if moving left is valid
-    let left_direction = turn_left(direction);
-    let left_location = Coord {
-        x: location.x + left_direction.x,
-        y: location.y + left_direction.y,
-    };
-    if (location == &Coord { x: 0, y: 0 } || straight_line_steps >= &min_steps)
-        && map.is_within(&left_location)
-    {
-        let heatloss = map.get_value(&left_location).unwrap() as u32;
-        valid_directions.push((
-            Position {
-                direction: left_direction,
-                location: left_location,
-                straight_line_steps: 1,
-            },
-            heatloss,
-        ));
+        let can_turn = straight_line_steps >= &rules.min_steps
+            && rules.max_turns.is_none_or(|max_turns| turns < &max_turns);
+        if can_turn {
+            moves.push((turn_left(direction), true));
+            moves.push((turn_right(direction), true));
+            if rules.can_reverse {
+                moves.push((reverse(direction), true));
+            }
+        }
     }
 
-    // Check if moving right is valid
-    let right_direction = turn_right(direction);
-    let right_location = Coord {
-        x: location.x + right_direction.x,
-        y: location.y + right_direction.y,
-    };
-    if (location == &Coord { x: 0, y: 0 } || straight_line_steps >= &min_steps)
-        && map.is_within(&right_location)
-    {
-        let heatloss = map.get_value(&right_location).unwrap() as u32;
-        valid_directions.push((
-            Position {
-                direction: right_direction,
-                location: right_location,
-                straight_line_steps: 1,
-            },
-            heatloss,
-        ));
-    }
+    moves
+        .into_iter()
+        .filter_map(|(new_direction, is_turn)| {
+            let new_location = Coord {
+                x: location.x + new_direction.x,
+                y: location.y + new_direction.y,
+            };
+            if !map.is_within(&new_location) {
+                return None;
+            }
+
+            let heatloss = (rules.entry_cost)(map, &new_location, &new_direction);
+            let straight_line_steps = if new_direction == *direction {
+                straight_line_steps + 1
+            } else {
+                1
+            };
+            let turns = if is_turn && rules.max_turns.is_some() {
+                turns + 1
+            } else {
+                *turns
+            };
 
-    valid_directions
+            Some((
+                Position {
+                    direction: new_direction,
+                    location: new_location,
+                    straight_line_steps,
+                    turns,
+                },
+                heatloss,
+            ))
+        })
+        .collect()
 }
 
 fn turn_left(direction: &Coord) -> Coord {
@@ -153,3 +161,90 @@ fn turn_right(direction: &Coord) -> Coord {
         y: -direction.x,
     }
 }
+
+fn reverse(direction: &Coord) -> Coord {
+    Coord {
+        x: -direction.x,
+        y: -direction.y,
+    }
+}
+
+fn get_start(rules: &Rules) -> Position {
+    Position {
+        direction: Coord { x: 1, y: 0 },
+        location: rules.get_start(),
+        straight_line_steps: 0,
+        turns: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ones(width: usize, height: usize) -> Map {
+        Map {
+            data: vec![vec![1; width]; height],
+        }
+    }
+
+    #[test]
+    fn test_can_reverse() {
+        let map = ones(7, 1);
+        let mut rules = Rules::new(0, 3);
+        assert!(find_minimal_heatloss(&map, &rules).is_none());
+
+        rules.can_reverse = true;
+        let (path, heatloss) = find_minimal_heatloss(&map, &rules).unwrap();
+        assert_eq!(path.last().unwrap().location, Coord { x: 6, y: 0 });
+        assert_eq!(heatloss, 10);
+    }
+
+    #[test]
+    fn test_max_turns() {
+        let map = ones(3, 3);
+        let mut rules = Rules::new(0, 3);
+
+        rules.max_turns = Some(0);
+        assert!(find_minimal_heatloss(&map, &rules).is_none());
+
+        rules.max_turns = Some(1);
+        let (path, heatloss) = find_minimal_heatloss(&map, &rules).unwrap();
+        assert_eq!(heatloss, 4);
+        assert_eq!(path.last().unwrap().turns, 1);
+    }
+
+    #[test]
+    fn test_entry_cost() {
+        let map = ones(3, 3);
+        let mut rules = Rules::new(0, 3);
+        rules.entry_cost = |map, location, direction| {
+            let heatloss = map.get_value(location).unwrap() as u32;
+            if direction.y != 0 {
+                heatloss * 10
+            } else {
+                heatloss
+            }
+        };
+
+        let (_, heatloss) = find_minimal_heatloss(&map, &rules).unwrap();
+        assert_eq!(heatloss, 22);
+        let (_, heatloss) = find_minimal_heatloss_astar(&map, &rules).unwrap();
+        assert_eq!(heatloss, 22);
+    }
+
+    #[test]
+    fn test_start_and_end() {
+        let map: Map = include_str!("example.txt").parse().unwrap();
+        let mut rules = Rules::new(0, 3);
+        rules.start = Some(Coord { x: 12, y: 12 });
+        rules.end = Some(Coord { x: 0, y: 0 });
+
+        let (path, heatloss) = find_minimal_heatloss(&map, &rules).unwrap();
+        assert_eq!(path.first().unwrap().location, Coord { x: 12, y: 12 });
+        assert_eq!(path.last().unwrap().location, Coord { x: 0, y: 0 });
+
+        let (_, astar_heatloss) = find_minimal_heatloss_astar(&map, &rules).unwrap();
+        assert_eq!(heatloss, astar_heatloss);
+    }
+}
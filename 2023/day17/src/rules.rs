@@ -0,0 +1,54 @@
+use crate::coord::Coord;
+use crate::map::Map;
+
+/// How the crucible is allowed to move through the city
+#[derive(Clone)]
+pub struct Rules {
+    /// Blocks to move in a straight line before turning or stopping
+    pub min_steps: u32,
+    /// Blocks allowed in a straight line before having to turn
+    pub max_steps: u32,
+    /// Whether the crucible can turn around, which counts as a turn
+    pub can_reverse: bool,
+    /// Most turns allowed over the whole trip, if limited
+    pub max_turns: Option<u32>,
+    /// Where the crucible starts, defaults to the top left
+    pub start: Option<Coord>,
+    /// Where the crucible needs to get to, defaults to the bottom right
+    pub end: Option<Coord>,
+    /// Heat lost when entering a block, given the block and the direction of travel
+    pub entry_cost: fn(&Map, &Coord, &Coord) -> u32,
+}
+
+impl Rules {
+    pub fn new(min_steps: u32, max_steps: u32) -> Self {
+        Rules {
+            min_steps,
+            max_steps,
+            can_reverse: false,
+            max_turns: None,
+            start: None,
+            end: None,
+            entry_cost: heatloss,
+        }
+    }
+
+    pub fn get_start(&self) -> Coord {
+        self.start.clone().unwrap_or(Coord { x: 0, y: 0 })
+    }
+
+    pub fn get_end(&self, map: &Map) -> Coord {
+        self.end.clone().unwrap_or_else(|| {
+            let size = map.get_size();
+            Coord {
+                x: size.x - 1,
+                y: size.y - 1,
+            }
+        })
+    }
+}
+
+/// The regular cost, which is just the value on the map regardless of direction
+pub fn heatloss(map: &Map, location: &Coord, _direction: &Coord) -> u32 {
+    map.get_value(location).unwrap_or_default() as u32
+}
@@ -1,13 +1,19 @@
+use std::{env, fs};
+
 use anyhow::Result;
 
 use instruction::Instruction;
 use map::Map;
+use point::Point;
+use polygon::Polygon;
 
 mod direction;
 mod instruction;
 mod map;
 mod point;
+mod polygon;
 mod rectangle;
+mod svg;
 
 fn part_1(input: &str) -> Result<u64> {
     let instructions = Instruction::parse_set(input)?;
@@ -21,10 +27,90 @@ fn part_2(input: &str) -> Result<u64> {
     Ok(map.count_filled())
 }
 
+fn export(input: &str, dir: &str) -> Result<()> {
+    let instructions = Instruction::parse_set(input)?;
+    fs::write(
+        format!("{}/part_1.svg", dir),
+        svg::render(&instructions, 1000.0),
+    )?;
+
+    let instructions = Instruction::parse_set_from_color(input)?;
+    fs::write(
+        format!("{}/part_2.svg", dir),
+        svg::render(&instructions, 1000.0),
+    )?;
+
+    Ok(())
+}
+
+/// Works out the lagoon for both dig plans from the polygon they trace, as a check on the sweep
+fn describe(input: &str) -> Result<String> {
+    let mut output = String::new();
+    for (name, instructions) in [
+        ("Part 1", Instruction::parse_set(input)?),
+        ("Part 2", Instruction::parse_set_from_color(input)?),
+    ] {
+        let polygon = Polygon::new(&instructions);
+        output += &format!(
+            "{name}: {} vertices, area {}, boundary {}, interior {}, filled {}, {} rectangles{}\n",
+            polygon.vertices().len(),
+            polygon.area(),
+            polygon.boundary_length(),
+            polygon.interior_points(),
+            polygon.count_filled(),
+            polygon.to_rectangles().len(),
+            if polygon.is_self_intersecting() {
+                ", self-intersecting"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(output)
+}
+
+/// Whether a point, measured from where digging starts, gets dug out in each dig plan
+fn contains(input: &str, x: &str, y: &str) -> Result<String> {
+    let point = Point::new(x.parse()?, y.parse()?);
+    let mut output = String::new();
+    for (name, instructions) in [
+        ("Part 1", Instruction::parse_set(input)?),
+        ("Part 2", Instruction::parse_set_from_color(input)?),
+    ] {
+        let is_dug = Polygon::new(&instructions).contains(&point);
+        output += &format!(
+            "{name}: ({}, {}) is {}\n",
+            point.x,
+            point.y,
+            if is_dug { "dug out" } else { "left alone" }
+        );
+    }
+    Ok(output)
+}
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().skip(1).map(|arg| arg.as_str()).collect();
     let input = include_str!("input.txt");
-    println!("Part 1: {:?}", part_1(input));
-    println!("Part 2: {:?}", part_2(input));
+
+    // Pass `describe` to check the polygon, `contains <x> <y>` to check a point, or
+    // `export <dir>` to draw both dig plans for a visual check
+    match args[..] {
+        [] => {
+            println!("Part 1: {:?}", part_1(input));
+            println!("Part 2: {:?}", part_2(input));
+        }
+        ["describe"] => match describe(input) {
+            Ok(description) => print!("{description}"),
+            Err(error) => println!("Describe: {error:?}"),
+        },
+        ["contains", x, y] => match contains(input, x, y) {
+            Ok(output) => print!("{output}"),
+            Err(error) => println!("Contains: {error:?}"),
+        },
+        ["export", dir] => println!("Export: {:?}", export(input, dir)),
+        _ => println!("Unknown arguments, expected describe, contains <x> <y> or export <dir>"),
+    }
 }
 
 #[cfg(test)]
@@ -44,4 +130,18 @@ mod tests {
         let result = part_2(input);
         assert_eq!(952408144115, result.unwrap());
     }
+
+    #[test]
+    fn test_contains() {
+        let input = include_str!("example.txt");
+        let expected = "\
+Part 1: (1, 1) is dug out
+Part 2: (1, 1) is dug out
+";
+        assert_eq!(expected, contains(input, "1", "1").unwrap());
+        assert!(contains(input, "-1", "0")
+            .unwrap()
+            .starts_with("Part 1: (-1, 0) is left alone\n"));
+        assert!(contains(input, "x", "0").is_err());
+    }
 }
@@ -0,0 +1,274 @@
+use std::collections::BTreeSet;
+
+use crate::{instruction::Instruction, point::Point, rectangle::Rectangle};
+
+/// The closed loop traced by a dig plan, with a vertex at the centre of every trench corner
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new<'a, I>(instructions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Instruction>,
+    {
+        let mut vertices = vec![Point::new(0, 0)];
+
+        let mut x = 0i64;
+        let mut y = 0i64;
+
+        for instruction in instructions {
+            x += instruction.direction.x as i64 * instruction.length as i64;
+            y += instruction.direction.y as i64 * instruction.length as i64;
+            vertices.push(Point::new(x, y));
+        }
+
+        // A closed plan ends where it started, which is already the first vertex
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Every edge as a pair of points, including the one that closes the loop
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Area enclosed by the vertices using the shoelace formula
+    pub fn area(&self) -> u64 {
+        let twice_area: i64 = self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum();
+        twice_area.unsigned_abs() / 2
+    }
+
+    /// Number of trench cubes along the loop
+    pub fn boundary_length(&self) -> u64 {
+        self.edges()
+            .map(|(a, b)| a.x.abs_diff(b.x) + a.y.abs_diff(b.y))
+            .sum()
+    }
+
+    /// Number of cubes strictly inside the trench, using Pick's theorem. A plan that encloses
+    /// nothing, like one that walks out and straight back, has no inside at all.
+    pub fn interior_points(&self) -> u64 {
+        // A = I + B / 2 - 1
+        (self.area() + 1).saturating_sub(self.boundary_length() / 2)
+    }
+
+    /// Number of cubes dug out, both the trench and the interior
+    pub fn count_filled(&self) -> u64 {
+        self.interior_points() + self.boundary_length()
+    }
+
+    /// Whether the point is on the trench or inside of it
+    pub fn contains(&self, point: &Point) -> bool {
+        let mut inside = false;
+
+        for (a, b) in self.edges() {
+            if within(point, &a, &b) {
+                return true;
+            }
+
+            // Cast a ray to the right, using a half open span so corners are only crossed once
+            if a.x == b.x && a.x > point.x && (a.y.min(b.y)..a.y.max(b.y)).contains(&point.y) {
+                inside = !inside;
+            }
+        }
+
+        inside
+    }
+
+    /// Whether any edge touches another, other than neighbours sharing their corner
+    pub fn is_self_intersecting(&self) -> bool {
+        let edges: Vec<(Point, Point)> = self.edges().collect();
+        let count = edges.len();
+
+        (0..count).any(|i| {
+            (i + 1..count).any(|j| {
+                let adjacent = j == i + 1 || (i == 0 && j == count - 1);
+                match overlap(&edges[i], &edges[j]) {
+                    Some((min, max)) => !adjacent || min != max,
+                    None => false,
+                }
+            })
+        })
+    }
+
+    /// Splits the enclosed area into non-overlapping rectangles, one horizontal slab at a time
+    pub fn to_rectangles(&self) -> Vec<Rectangle> {
+        let ys: BTreeSet<i64> = self.vertices.iter().map(|p| p.y).collect();
+        let ys: Vec<i64> = ys.into_iter().collect();
+        let mut rectangles = Vec::new();
+
+        for slab in ys.windows(2) {
+            let (top, bottom) = (slab[0], slab[1]);
+
+            // Vertical edges that span the whole slab
+            let mut xs: Vec<i64> = self
+                .edges()
+                .filter(|(a, b)| a.x == b.x && a.y.min(b.y) <= top && a.y.max(b.y) >= bottom)
+                .map(|(a, _)| a.x)
+                .collect();
+            xs.sort();
+
+            for pair in xs.chunks_exact(2) {
+                rectangles.push(Rectangle::new(
+                    pair[0],
+                    top,
+                    (pair[1] - pair[0]) as u64,
+                    (bottom - top) as u64,
+                ));
+            }
+        }
+
+        rectangles
+    }
+}
+
+// Private
+
+fn within(point: &Point, a: &Point, b: &Point) -> bool {
+    (a.x.min(b.x)..=a.x.max(b.x)).contains(&point.x)
+        && (a.y.min(b.y)..=a.y.max(b.y)).contains(&point.y)
+}
+
+/// The corners of the box where two axis-aligned edges overlap, if they do
+fn overlap(edge: &(Point, Point), other: &(Point, Point)) -> Option<(Point, Point)> {
+    let min = Point::new(
+        edge.0.x.min(edge.1.x).max(other.0.x.min(other.1.x)),
+        edge.0.y.min(edge.1.y).max(other.0.y.min(other.1.y)),
+    );
+    let max = Point::new(
+        edge.0.x.max(edge.1.x).min(other.0.x.max(other.1.x)),
+        edge.0.y.max(edge.1.y).min(other.0.y.max(other.1.y)),
+    );
+
+    if min.x <= max.x && min.y <= max.y {
+        Some((min, max))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::Map;
+
+    use super::*;
+
+    fn polygon(input: &str) -> Polygon {
+        Polygon::new(&Instruction::parse_set(input).unwrap())
+    }
+
+    #[test]
+    fn test_example() {
+        let polygon = polygon(include_str!("example.txt"));
+
+        assert_eq!(polygon.vertices().len(), 14);
+        assert_eq!(polygon.area(), 42);
+        assert_eq!(polygon.boundary_length(), 38);
+        assert_eq!(polygon.interior_points(), 24);
+        assert_eq!(polygon.count_filled(), 62);
+        assert!(!polygon.is_self_intersecting());
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let there_and_back = polygon("R 2 (#000000)\nL 2 (#000000)");
+        assert_eq!(there_and_back.area(), 0);
+        assert_eq!(there_and_back.interior_points(), 0);
+    }
+
+    #[test]
+    fn test_matches_sweep() {
+        let input = include_str!("example.txt");
+
+        let instructions = Instruction::parse_set(input).unwrap();
+        let polygon = Polygon::new(&instructions);
+        assert_eq!(
+            polygon.count_filled(),
+            Map::new(instructions).count_filled()
+        );
+
+        let instructions = Instruction::parse_set_from_color(input).unwrap();
+        let polygon = Polygon::new(&instructions);
+        assert_eq!(
+            polygon.count_filled(),
+            Map::new(instructions).count_filled()
+        );
+        assert_eq!(polygon.count_filled(), 952408144115);
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon = polygon(include_str!("example.txt"));
+
+        assert!(polygon.contains(&Point::new(0, 0)));
+        assert!(polygon.contains(&Point::new(3, 3)));
+        assert!(polygon.contains(&Point::new(6, 5)));
+        assert!(!polygon.contains(&Point::new(1, 3)));
+        assert!(!polygon.contains(&Point::new(7, 0)));
+        assert!(!polygon.contains(&Point::new(-1, 2)));
+
+        // Every cell inside the bounding box is either dug or not
+        let count = (0..=9)
+            .flat_map(|y| (0..=6).map(move |x| Point::new(x, y)))
+            .filter(|point| polygon.contains(point))
+            .count();
+        assert_eq!(count, 62);
+    }
+
+    #[test]
+    fn test_is_self_intersecting() {
+        let figure_eight = polygon(
+            "\
+R 4 (#000000)
+D 2 (#000000)
+L 2 (#000000)
+U 4 (#000000)
+L 2 (#000000)
+D 2 (#000000)",
+        );
+        assert!(figure_eight.is_self_intersecting());
+
+        let doubling_back = polygon(
+            "\
+R 4 (#000000)
+L 2 (#000000)
+D 2 (#000000)
+L 2 (#000000)
+U 2 (#000000)",
+        );
+        assert!(doubling_back.is_self_intersecting());
+    }
+
+    #[test]
+    fn test_to_rectangles() {
+        let input = include_str!("example.txt");
+
+        for instructions in [
+            Instruction::parse_set(input).unwrap(),
+            Instruction::parse_set_from_color(input).unwrap(),
+        ] {
+            let polygon = Polygon::new(&instructions);
+            let rectangles = polygon.to_rectangles();
+
+            let area: u64 = rectangles.iter().map(|r| r.area()).sum();
+            assert_eq!(area, polygon.area());
+
+            for (i, rect) in rectangles.iter().enumerate() {
+                for other in rectangles[i + 1..].iter() {
+                    assert_eq!(rect.intersection(other), 0);
+                }
+            }
+        }
+    }
+}
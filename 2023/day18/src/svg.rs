@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use crate::{instruction::Instruction, point::Point, polygon::Polygon};
+
+/// Empty space left around the drawing, in pixels
+const MARGIN: f64 = 10.0;
+
+/// Draws the dig plan as an SVG image, with the trench in each instruction's colour
+/// over a filled interior. The plan is scaled so its largest side fits within `size` pixels.
+pub fn render(instructions: &[Instruction], size: f64) -> String {
+    let polygon = Polygon::new(instructions);
+    let vertices = polygon.vertices();
+
+    let min_x = vertices.iter().map(|p| p.x).min().unwrap_or_default();
+    let min_y = vertices.iter().map(|p| p.y).min().unwrap_or_default();
+    let max_x = vertices.iter().map(|p| p.x).max().unwrap_or_default();
+    let max_y = vertices.iter().map(|p| p.y).max().unwrap_or_default();
+
+    // Each trench cube is one unit wide, so add one to fit the outer half of the border cubes
+    let extent = ((max_x - min_x).max(max_y - min_y) + 1) as f64;
+    let scale = size / extent;
+    let project = |point: &Point| {
+        (
+            MARGIN + (point.x - min_x) as f64 * scale + scale / 2.0,
+            MARGIN + (point.y - min_y) as f64 * scale + scale / 2.0,
+        )
+    };
+
+    let width = (max_x - min_x + 1) as f64 * scale + MARGIN * 2.0;
+    let height = (max_y - min_y + 1) as f64 * scale + MARGIN * 2.0;
+    // Huge plans would otherwise have a trench thinner than a pixel
+    let stroke_width = scale.max(1.0);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.2} {:.2}">"#,
+        width.ceil(),
+        height.ceil(),
+        width,
+        height
+    )
+    .unwrap();
+
+    let points: Vec<String> = vertices
+        .iter()
+        .map(|point| {
+            let (x, y) = project(point);
+            format!("{:.2},{:.2}", x, y)
+        })
+        .collect();
+    writeln!(
+        svg,
+        r##"  <polygon points="{}" fill="#d0d0d0" stroke="none" />"##,
+        points.join(" ")
+    )
+    .unwrap();
+
+    for ((a, b), instruction) in polygon.edges().zip(instructions) {
+        let (x1, y1) = project(&a);
+        let (x2, y2) = project(&b);
+        writeln!(
+            svg,
+            r##"  <line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="#{:06x}" stroke-width="{:.2}" stroke-linecap="square" />"##,
+            x1, y1, x2, y2, instruction.color, stroke_width
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let instructions = Instruction::parse_set(include_str!("example.txt")).unwrap();
+        let svg = render(&instructions, 100.0);
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="90" height="120""#)
+        );
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polygon").count(), 1);
+        assert_eq!(svg.matches("<line").count(), 14);
+        assert!(svg.contains(
+            r##"<line x1="15.00" y1="15.00" x2="75.00" y2="15.00" stroke="#70c710" stroke-width="10.00""##
+        ));
+        assert!(svg.contains(r##"stroke="#7a21e3""##));
+    }
+
+    #[test]
+    fn test_render_scales_huge_plans() {
+        let instructions = Instruction::parse_set_from_color(include_str!("example.txt")).unwrap();
+        let svg = render(&instructions, 500.0);
+
+        assert!(svg.contains(r#"stroke-width="1.00""#));
+
+        // Every coordinate lands within the image
+        let coordinates = svg
+            .split('"')
+            .filter_map(|value| value.split(',').next()?.parse::<f64>().ok());
+        assert!(coordinates.clone().count() > 0);
+        assert!(coordinates.into_iter().all(|value| value <= 520.0));
+    }
+}
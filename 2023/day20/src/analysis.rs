@@ -0,0 +1,203 @@
+use crate::{
+    configuration::Configuration,
+    module::{Module, ModuleType},
+};
+
+/// A chain of flip-flops that counts button presses in binary. Each flip-flop passes its low
+/// pulse on to the next when it turns off, like carrying a bit. The ones that are set at the
+/// target count feed the conjunction, so it only goes off once they're all on, and it then
+/// resets the chain by pulsing the flip-flops that were still off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter {
+    /// The flip-flops from the lowest bit up, starting from the one the broadcaster feeds
+    pub flip_flops: Vec<String>,
+    /// The conjunction the chain feeds
+    pub conjunction: String,
+    /// The count the conjunction goes off at, with a bit set for each flip-flop that feeds it
+    pub value: usize,
+}
+
+impl Counter {
+    /// Follows a chain of flip-flops from one the broadcaster feeds, as long as it's counter-like
+    fn follow(config: &Configuration, start: &str) -> Option<Self> {
+        let mut flip_flops = Vec::new();
+        let mut conjunction: Option<&str> = None;
+        let mut value = 0;
+        let mut current = config.get_module(start)?;
+
+        loop {
+            if !matches!(current.module_type, ModuleType::FlipFlop(_)) {
+                return None;
+            }
+
+            let (next, conjunctions): (Vec<&Module>, Vec<&Module>) = current
+                .outputs
+                .iter()
+                .filter_map(|output| config.get_module(output))
+                .partition(|module| matches!(module.module_type, ModuleType::FlipFlop(_)));
+
+            // Every flip-flop that feeds a conjunction has to feed the same one
+            match conjunctions[..] {
+                [] => {}
+                [module] if conjunction.is_none_or(|name| name == module.name) => {
+                    conjunction = Some(&module.name);
+                    value |= 1 << flip_flops.len();
+                }
+                _ => return None,
+            }
+            flip_flops.push(current.name.clone());
+
+            match next[..] {
+                [] => break,
+                [module] if !flip_flops.contains(&module.name) => current = module,
+                _ => return None,
+            }
+        }
+
+        // The conjunction resets the count by pulsing every flip-flop that isn't set
+        let conjunction = config.get_module(conjunction?)?;
+        let resets_chain = flip_flops
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| value & (1 << bit) == 0)
+            .all(|(_, name)| conjunction.outputs.contains(name));
+        if !resets_chain {
+            return None;
+        }
+
+        // Nothing else can be pulsing the flip-flops, or they wouldn't count
+        for (bit, name) in flip_flops.iter().enumerate() {
+            let previous = if bit == 0 {
+                "broadcaster"
+            } else {
+                &flip_flops[bit - 1]
+            };
+            let is_fed_by_others = config.modules().any(|module| {
+                module.outputs.contains(name)
+                    && module.name != previous
+                    && module.name != conjunction.name
+            });
+            if is_fed_by_others {
+                return None;
+            }
+        }
+
+        Some(Counter {
+            flip_flops,
+            conjunction: conjunction.name.clone(),
+            value,
+        })
+    }
+}
+
+/// Finds the counters started by the broadcaster
+pub fn find_counters(config: &Configuration) -> Vec<Counter> {
+    let Some(broadcaster) = config.get_module("broadcaster") else {
+        return Vec::new();
+    };
+
+    broadcaster
+        .outputs
+        .iter()
+        .filter_map(|start| Counter::follow(config, start))
+        .collect()
+}
+
+/// Draws the network in Graphviz's DOT format, with a different shape for each module type
+pub fn to_dot(config: &Configuration) -> String {
+    let mut modules: Vec<&Module> = config.modules().collect();
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut dot = String::from("digraph configuration {\n");
+    for module in &modules {
+        let style = match module.module_type {
+            ModuleType::Broadcaster => "shape=doublecircle",
+            ModuleType::FlipFlop(_) => "shape=box",
+            ModuleType::Conjuction(_) => "shape=diamond, style=filled",
+        };
+        dot += &format!("    \"{}\" [{style}];\n", module.name);
+    }
+    for module in &modules {
+        for output in &module.outputs {
+            dot += &format!("    \"{}\" -> \"{output}\";\n", module.name);
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER: &str = "\
+broadcaster -> a
+%a -> b, hub
+%b -> c
+%c -> hub
+&hub -> b, a, inv
+&inv -> out
+&out -> rx";
+
+    #[test]
+    fn test_find_counters() {
+        let config: Configuration = COUNTER.parse().unwrap();
+        let expected = Counter {
+            flip_flops: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            conjunction: "hub".to_string(),
+            value: 0b101,
+        };
+        assert_eq!(vec![expected], find_counters(&config));
+
+        // The count is when rx first gets a low pulse
+        let mut config = config;
+        assert_eq!(5, config.get_min_presses_rx());
+    }
+
+    #[test]
+    fn test_not_counters() {
+        // The example chains either feed more than one conjunction, never get reset, or get
+        // pulsed from somewhere else
+        for input in [
+            include_str!("../example1.txt"),
+            include_str!("../example2.txt"),
+        ] {
+            let config: Configuration = input.parse().unwrap();
+            assert!(find_counters(&config).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_input_counters() {
+        let mut config: Configuration = include_str!("../input.txt").parse().unwrap();
+        let counters = find_counters(&config);
+        assert_eq!(4, counters.len());
+        assert!(counters
+            .iter()
+            .all(|counter| counter.flip_flops.len() == 12));
+
+        let values = counters.iter().map(|counter| counter.value).collect();
+        assert_eq!(crate::math::lcm(values), config.get_min_presses_rx());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let config: Configuration = include_str!("../example2.txt").parse().unwrap();
+        let expected = "\
+digraph configuration {
+    \"a\" [shape=box];
+    \"b\" [shape=box];
+    \"broadcaster\" [shape=doublecircle];
+    \"con\" [shape=diamond, style=filled];
+    \"inv\" [shape=diamond, style=filled];
+    \"a\" -> \"inv\";
+    \"a\" -> \"con\";
+    \"b\" -> \"con\";
+    \"broadcaster\" -> \"a\";
+    \"con\" -> \"output\";
+    \"inv\" -> \"b\";
+}
+";
+        assert_eq!(expected, to_dot(&config));
+    }
+}
@@ -48,6 +48,14 @@ impl Configuration {
         }
     }
 
+    pub fn get_module(&self, name: &str) -> Option<&Module> {
+        self.state.get(name)
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.state.values()
+    }
+
     pub fn get_total_pulses(&self) -> usize {
         self.pulses[&Pulse::Low] * self.pulses[&Pulse::High]
     }
@@ -1,5 +1,8 @@
+use std::env;
+
 use configuration::Configuration;
 
+mod analysis;
 mod configuration;
 mod math;
 mod module;
@@ -19,10 +22,37 @@ fn part_2(input: &str) -> usize {
     config.get_min_presses_rx()
 }
 
+/// Explains part 2 by listing the counters that feed into rx
+fn analyse(input: &str) -> String {
+    let config: Configuration = input.parse().unwrap();
+    let counters = analysis::find_counters(&config);
+    let mut output = String::new();
+    for counter in &counters {
+        output += &format!(
+            "{} counts to {:b} ({}) through {}\n",
+            counter.conjunction,
+            counter.value,
+            counter.value,
+            counter.flip_flops.join(" -> ")
+        );
+    }
+    let values = counters.iter().map(|counter| counter.value).collect();
+    output += &format!("Lcm: {}\n", math::lcm(values));
+    output
+}
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let input = include_str!("../input.txt");
-    println!("Part 1: {:?}", part_1(input));
-    println!("Part 2: {:?}", part_2(input));
+
+    match args.get(1).map(|s| s.as_str()) {
+        Some("analyse") => print!("{}", analyse(input)),
+        Some("dot") => print!("{}", analysis::to_dot(&input.parse().unwrap())),
+        _ => {
+            println!("Part 1: {:?}", part_1(input));
+            println!("Part 2: {:?}", part_2(input));
+        }
+    }
 }
 
 #[cfg(test)]
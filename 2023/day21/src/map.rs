@@ -1,4 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+
+/// Largest number of tiles out from the start to search before giving up on distances settling.
+/// Gardens with open edges settle within a couple of tiles, and this keeps the search to a few
+/// million spaces even for a large garden.
+const MAX_RADIUS: usize = 8;
 
 #[derive(Clone, Debug)]
 pub struct Map {
@@ -22,7 +27,33 @@ impl Map {
         set.len()
     }
 
-    pub fn get_num_spaces_2(&self, distance: usize) -> usize {
+    /// Uses the quadratic fit when the garden allows it, otherwise counts tile by tile
+    pub fn get_num_spaces_2(&self, distance: usize) -> Option<usize> {
+        if self.can_fit_quadratic(distance) {
+            Some(self.get_num_spaces_quadratic(distance))
+        } else {
+            self.get_num_spaces_tiled(distance)
+        }
+    }
+
+    /// Whether the diamond of reachable spaces lines up with the tiles, which is what makes the
+    /// number of spaces grow quadratically with every `size` steps.
+    pub fn can_fit_quadratic(&self, distance: usize) -> bool {
+        let middle = (self.size / 2) as isize;
+
+        let is_square = self.grid.iter().all(|row| row.len() == self.size);
+        let is_start_centred = self.start.row == middle && self.start.col == middle;
+        let is_centre_clear = self.grid[middle as usize].iter().all(|&empty| empty)
+            && self.grid.iter().all(|row| row[middle as usize]);
+
+        is_square
+            && self.size % 2 == 1
+            && is_start_centred
+            && is_centre_clear
+            && distance % self.size == self.size / 2
+    }
+
+    pub fn get_num_spaces_quadratic(&self, distance: usize) -> usize {
         let grids = distance / self.size;
         let rem = distance % self.size;
         let mut set = HashSet::from([self.start]);
@@ -49,6 +80,108 @@ impl Map {
         a * (grids * grids) + b * grids + c
     }
 
+    /// Counts spaces for any distance. Distances are searched across the tiles around the start
+    /// until every tile on the edge is exactly `size` steps further than its inner neighbour, at
+    /// which point every tile further out follows the same pattern and can be counted directly.
+    /// The search widens one tile at a time, so it never covers more tiles than it needs to.
+    pub fn get_num_spaces_tiled(&self, distance: usize) -> Option<usize> {
+        (1..=MAX_RADIUS).find_map(|radius| {
+            let tiles = self.get_tile_distances(radius);
+            self.is_settled(&tiles)
+                .then(|| self.count_tiles(&tiles, distance))
+        })
+    }
+
+    /// Distance from the start to every space across `(2 * radius + 1)^2` tiles
+    fn get_tile_distances(&self, radius: usize) -> TileDistances {
+        let mut tiles = TileDistances::new(radius, self.size);
+        let length = tiles.length;
+        let start = (
+            radius * self.size + self.start.row as usize,
+            radius * self.size + self.start.col as usize,
+        );
+        tiles.set(start, 0);
+
+        let mut queue = VecDeque::from([start]);
+        while let Some((row, col)) = queue.pop_front() {
+            let next = tiles.distances[row * length + col] + 1;
+            for (d_row, d_col) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let row = row as isize + d_row;
+                let col = col as isize + d_col;
+                if row < 0 || col < 0 || row >= length as isize || col >= length as isize {
+                    continue;
+                }
+
+                let (row, col) = (row as usize, col as usize);
+                if self.grid[row % self.size][col % self.size]
+                    && tiles.distances[row * length + col] == UNREACHED
+                {
+                    tiles.set((row, col), next);
+                    queue.push_back((row, col));
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// Whether every edge tile is one whole tile further away than the tile inside of it
+    fn is_settled(&self, tiles: &TileDistances) -> bool {
+        let r = tiles.radius as isize;
+        let is_one_tile_further = |tile: (isize, isize), inner: (isize, isize)| {
+            self.get_spaces().all(|space| {
+                let outer = tiles.get(tile, space);
+                let inner = tiles.get(inner, space);
+                outer == inner.map(|d| d + self.size)
+            })
+        };
+
+        (-r..=r).all(|t| {
+            let inner = t - t.signum();
+            is_one_tile_further((t, r), (t, r - 1))
+                && is_one_tile_further((t, -r), (t, 1 - r))
+                && is_one_tile_further((r, t), (r - 1, t))
+                && is_one_tile_further((-r, t), (1 - r, t))
+                && (t.abs() != r
+                    || (is_one_tile_further((t, r), (inner, r))
+                        && is_one_tile_further((t, -r), (inner, -r))))
+        })
+    }
+
+    fn count_tiles(&self, tiles: &TileDistances, distance: usize) -> usize {
+        let r = tiles.radius as isize;
+        let mut count = 0;
+
+        for tile_row in -r..=r {
+            for tile_col in -r..=r {
+                let is_row_edge = tile_row.abs() == r;
+                let is_col_edge = tile_col.abs() == r;
+
+                for space in self.get_spaces() {
+                    let Some(d) = tiles.get((tile_row, tile_col), space) else {
+                        continue;
+                    };
+
+                    count += match (is_row_edge, is_col_edge) {
+                        // Tiles inside of the edges are counted as they are
+                        (false, false) => is_reachable(d, distance) as usize,
+                        // Corner tiles stand in for the whole quadrant beyond them, where
+                        // there are `n + 1` tiles another `n` tiles further away
+                        (true, true) => count_repeats(d, distance, self.size, true),
+                        // Other edge tiles stand in for the whole line of tiles beyond them
+                        _ => count_repeats(d, distance, self.size, false),
+                    };
+                }
+            }
+        }
+
+        count
+    }
+
+    fn get_spaces(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.size).flat_map(move |row| (0..self.size).map(move |col| (row, col)))
+    }
+
     fn get_neighbors(&self, position: &Coord) -> HashSet<Coord> {
         [(-1, 0), (1, 0), (0, -1), (0, 1)]
             .into_iter()
@@ -78,6 +211,79 @@ impl Map {
     }
 }
 
+/// Marks a space the search never reached
+const UNREACHED: u32 = u32::MAX;
+
+/// Distances from the start across a square of tiles, kept in one flat list since there can be
+/// millions of them
+struct TileDistances {
+    radius: usize,
+    size: usize,
+    /// How many spaces across the whole square is
+    length: usize,
+    distances: Vec<u32>,
+}
+
+impl TileDistances {
+    fn new(radius: usize, size: usize) -> Self {
+        let length = (2 * radius + 1) * size;
+        TileDistances {
+            radius,
+            size,
+            length,
+            distances: vec![UNREACHED; length * length],
+        }
+    }
+
+    fn set(&mut self, (row, col): (usize, usize), distance: u32) {
+        self.distances[row * self.length + col] = distance;
+    }
+
+    /// Distance to a space within a tile, where tile `(0, 0)` holds the start
+    fn get(&self, tile: (isize, isize), space: (usize, usize)) -> Option<usize> {
+        let row = (tile.0 + self.radius as isize) as usize * self.size + space.0;
+        let col = (tile.1 + self.radius as isize) as usize * self.size + space.1;
+        match self.distances[row * self.length + col] {
+            UNREACHED => None,
+            distance => Some(distance as usize),
+        }
+    }
+}
+
+/// Whether a space `d` steps away can be stood on after exactly `distance` steps, which is
+/// possible whenever there are an even number of steps left over to step back and forth
+fn is_reachable(d: usize, distance: usize) -> bool {
+    d <= distance && (distance - d).is_multiple_of(2)
+}
+
+/// Counts the reachable copies of a space `d` steps away, repeated every `size` steps further.
+/// When `is_quadrant` is set, the `n`th repeat is weighted as `n + 1` tiles.
+fn count_repeats(d: usize, distance: usize, size: usize, is_quadrant: bool) -> usize {
+    if d > distance {
+        return 0;
+    }
+
+    let max_repeat = (distance - d) / size;
+    // Which repeats land on the right parity
+    let (first, step) = match (size.is_multiple_of(2), (distance - d).is_multiple_of(2)) {
+        (true, true) => (0, 1),
+        (true, false) => return 0,
+        (false, true) => (0, 2),
+        (false, false) => (1, 2),
+    };
+    if first > max_repeat {
+        return 0;
+    }
+
+    let n = (max_repeat - first) / step + 1;
+    if is_quadrant {
+        // Sum of `k + 1` for k = first, first + step, ...
+        n * (first + 1) + step * n * (n - 1) / 2
+    } else {
+        n
+    }
+}
+
 impl From<&str> for Map {
     fn from(s: &str) -> Self {
         let mut start = Coord { row: 0, col: 0 };
@@ -115,3 +321,76 @@ pub struct Coord {
     row: isize,
     col: isize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn test_example() {
+        let map: Map = EXAMPLE.into();
+        assert_eq!(map.get_num_spaces_1(6), 16);
+
+        assert!(!map.can_fit_quadratic(5000));
+        assert_eq!(map.get_num_spaces_2(6), Some(16));
+        assert_eq!(map.get_num_spaces_2(10), Some(50));
+        assert_eq!(map.get_num_spaces_2(50), Some(1594));
+        assert_eq!(map.get_num_spaces_2(100), Some(6536));
+        assert_eq!(map.get_num_spaces_2(500), Some(167004));
+        assert_eq!(map.get_num_spaces_2(1000), Some(668697));
+        assert_eq!(map.get_num_spaces_2(5000), Some(16733044));
+    }
+
+    #[test]
+    fn test_tiled_matches_brute_force() {
+        let map: Map = EXAMPLE.into();
+        for distance in [0, 1, 2, 7, 11, 16, 23, 33, 40] {
+            assert_eq!(
+                map.get_num_spaces_tiled(distance),
+                Some(map.get_num_spaces_1(distance)),
+                "distance {}",
+                distance
+            );
+        }
+    }
+
+    #[test]
+    fn test_quadratic_matches_tiled() {
+        let map: Map = "\
+...........
+......##.#.
+.###..#..#.
+..#.#...#..
+....#.#....
+.....S.....
+.##......#.
+.......##..
+.##.#.####.
+.##...#.##.
+..........."
+            .into();
+
+        for distance in [16, 27, 38, 115, 335] {
+            assert!(map.can_fit_quadratic(distance));
+            assert_eq!(
+                Some(map.get_num_spaces_quadratic(distance)),
+                map.get_num_spaces_tiled(distance)
+            );
+        }
+        assert_eq!(map.get_num_spaces_tiled(38), Some(map.get_num_spaces_1(38)));
+        assert!(!map.can_fit_quadratic(17));
+    }
+}
@@ -15,6 +15,15 @@ impl Brick {
         self.p1.z.min(self.p2.z)
     }
 
+    /// Every (x, y) cell this brick covers when viewed from above
+    pub fn footprint(&self) -> impl Iterator<Item = (usize, usize)> {
+        let min_x = self.p1.x.min(self.p2.x);
+        let max_x = self.p1.x.max(self.p2.x);
+        let min_y = self.p1.y.min(self.p2.y);
+        let max_y = self.p1.y.max(self.p2.y);
+        (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+    }
+
     pub fn will_collide(&self, other: &Self) -> bool {
         let self_min_x = self.p1.x.min(self.p2.x);
         let self_max_x = self.p1.x.max(self.p2.x);
@@ -122,4 +131,16 @@ mod tests {
         assert!(brick3.will_collide(&brick4));
         assert!(brick3.will_collide(&brick1));
     }
+
+    #[test]
+    fn test_footprint() {
+        let brick: Brick = "0,0,2~2,0,2".into();
+        assert_eq!(
+            brick.footprint().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 0)]
+        );
+
+        let brick: Brick = "1,1,8~1,1,9".into();
+        assert_eq!(brick.footprint().collect::<Vec<_>>(), vec![(1, 1)]);
+    }
 }
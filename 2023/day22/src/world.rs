@@ -3,6 +3,8 @@ use std::collections::{HashMap, HashSet};
 use crate::brick::Brick;
 
 pub struct World {
+    /// All settled bricks, sorted from the ground up
+    settled: Vec<Brick>,
     /// A map where the key is a supporting brick for the set
     bricks: HashMap<Brick, HashSet<Brick>>,
     /// A map where the key is supported by the set
@@ -14,93 +16,111 @@ impl World {
         let mut bricks_vec: Vec<Brick> = bricks.collect();
         bricks_vec.sort();
 
+        // The top-most settled brick at each (x, y) column
+        let mut height_map: HashMap<(usize, usize), Brick> = HashMap::new();
+        let mut bricks: HashMap<Brick, HashSet<Brick>> = HashMap::new();
+        let mut supported: HashMap<Brick, HashSet<Brick>> = HashMap::new();
+
         // Settle all the bricks
-        let mut settled: Vec<&Brick> = Vec::new();
         for brick in bricks_vec.iter_mut() {
-            let new_z = settled
+            let below: HashSet<Brick> = brick
+                .footprint()
+                .filter_map(|cell| height_map.get(&cell))
+                .copied()
+                .collect();
+            let new_z = below
                 .iter()
-                .filter(|&&other| brick.will_collide(other))
-                .map(|&other| other.max_z())
+                .map(|other| other.max_z())
                 .max()
                 .unwrap_or_default()
                 + 1;
             brick.move_to(new_z);
-            settled.push(brick);
+
+            // Only the highest bricks underneath are actually touching
+            let supporters: HashSet<Brick> = below
+                .into_iter()
+                .filter(|other| other.supports(brick))
+                .collect();
+            for supporter in supporters.iter() {
+                bricks.entry(*supporter).or_default().insert(*brick);
+            }
+            if !supporters.is_empty() {
+                supported.insert(*brick, supporters);
+            }
+
+            bricks.entry(*brick).or_default();
+            for cell in brick.footprint() {
+                height_map.insert(cell, *brick);
+            }
         }
 
-        // Build up the maps
-        let mut supported = HashMap::new();
-        let bricks = bricks_vec
-            .iter()
-            .enumerate()
-            .map(|(i, brick)| {
-                (
-                    *brick,
-                    // Find any bricks that this brick is supporting
-                    bricks_vec[i..]
-                        .iter()
-                        .filter(|supported_brick| brick.supports(supported_brick))
-                        .map(|supported_brick| {
-                            // Also add to the supported map
-                            supported
-                                .entry(*supported_brick)
-                                .or_insert(HashSet::new())
-                                .insert(*brick);
-                            *supported_brick
-                        })
-                        .collect(),
-                )
-            })
-            .collect();
+        bricks_vec.sort();
+        World {
+            settled: bricks_vec,
+            bricks,
+            supported,
+        }
+    }
 
-        World { bricks, supported }
+    /// All settled bricks, from the ground up
+    pub fn bricks(&self) -> impl Iterator<Item = &Brick> {
+        self.settled.iter()
+    }
+
+    /// The bricks directly underneath holding this brick up
+    pub fn supporters_of(&self, brick: &Brick) -> impl Iterator<Item = &Brick> {
+        self.supported.get(brick).into_iter().flatten()
+    }
+
+    /// The bricks resting directly on top of this brick
+    pub fn supported_by(&self, brick: &Brick) -> impl Iterator<Item = &Brick> {
+        self.bricks.get(brick).into_iter().flatten()
     }
 
     pub fn count_safe(&self) -> usize {
         // Go through each brick
-        self.bricks
-            .iter()
-            .filter(|(_, set)| {
+        self.bricks()
+            .filter(|brick| {
                 // Check all of the bricks that are supported by this brick
-                set.iter().all(|brick| {
+                self.supported_by(brick)
                     // If it's supported by more than 1, then we're good
-                    self.supported
-                        .get(brick)
-                        .map(|s| s.len())
-                        .unwrap_or_default()
-                        > 1
-                })
+                    .all(|other| self.supporters_of(other).count() > 1)
             })
             .count()
     }
 
     pub fn count_chain_reactions(&self) -> usize {
-        self.bricks
-            .keys()
-            .map(|brick| self.count_falling(brick))
+        self.rank_by_blast_radius()
+            .iter()
+            .map(|(_, count)| count)
             .sum()
     }
 
-    fn count_falling(&self, brick: &Brick) -> usize {
+    /// Every brick that would fall if this brick was disintegrated, not including itself
+    pub fn falling_if_removed(&self, brick: &Brick) -> HashSet<Brick> {
         // This is a set of falling bricks
         let mut falling = HashSet::from([*brick]);
-        // And cloning the supported map since we'll removing entries as we go along
-        let mut supported = self.supported.clone();
 
-        // Keep searching for more bricks that will fall
-        while let Some((&another, _)) = supported
-            .iter()
-            // If the entire supporting set is within the set of falling bricks
-            .find(|(_, set)| set.is_subset(&falling))
-        {
-            // It's also falling
-            falling.insert(another);
-            // And remove it from supported so we don't consider it again
-            supported.remove(&another);
+        // Bricks are sorted by height, so all supporters are visited before what they support
+        for other in self.bricks().filter(|other| other.min_z() > brick.min_z()) {
+            let mut supporters = self.supporters_of(other).peekable();
+            if supporters.peek().is_some() && supporters.all(|s| falling.contains(s)) {
+                falling.insert(*other);
+            }
         }
 
-        // Minus one because we're not counting the original brick
-        falling.len() - 1
+        falling.remove(brick);
+        falling
+    }
+
+    /// Every brick paired with how many others would fall if it were removed, largest first
+    pub fn rank_by_blast_radius(&self) -> Vec<(Brick, usize)> {
+        let mut ranking: Vec<(Brick, usize)> = self
+            .bricks()
+            .map(|brick| (*brick, self.falling_if_removed(brick).len()))
+            .collect();
+        ranking.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        ranking
     }
 }
 
@@ -109,3 +129,68 @@ impl From<&str> for World {
         Self::new(s.lines().map(|line| line.into()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn test_settling() {
+        let world: World = EXAMPLE.into();
+        let g: Brick = "1,1,5~1,1,6".into();
+        assert!(world.bricks().any(|brick| *brick == g));
+        assert_eq!(world.bricks().map(|b| b.max_z()).max(), Some(6));
+    }
+
+    #[test]
+    fn test_supports() {
+        let world: World = EXAMPLE.into();
+        let a: Brick = "1,0,1~1,2,1".into();
+        let b: Brick = "0,0,2~2,0,2".into();
+        let c: Brick = "0,2,2~2,2,2".into();
+        let d: Brick = "0,0,3~0,2,3".into();
+
+        assert_eq!(world.supporters_of(&a).count(), 0);
+        assert_eq!(
+            world.supported_by(&a).copied().collect::<HashSet<_>>(),
+            HashSet::from([b, c])
+        );
+        assert_eq!(
+            world.supporters_of(&d).copied().collect::<HashSet<_>>(),
+            HashSet::from([b, c])
+        );
+    }
+
+    #[test]
+    fn test_falling_if_removed() {
+        let world: World = EXAMPLE.into();
+        let a: Brick = "1,0,1~1,2,1".into();
+        let f: Brick = "0,1,4~2,1,4".into();
+        let g: Brick = "1,1,5~1,1,6".into();
+
+        assert_eq!(world.falling_if_removed(&a).len(), 6);
+        assert_eq!(world.falling_if_removed(&f), HashSet::from([g]));
+        assert!(world.falling_if_removed(&g).is_empty());
+    }
+
+    #[test]
+    fn test_rank_by_blast_radius() {
+        let world: World = EXAMPLE.into();
+        let ranking = world.rank_by_blast_radius();
+        let a: Brick = "1,0,1~1,2,1".into();
+        let f: Brick = "0,1,4~2,1,4".into();
+
+        assert_eq!(ranking[0], (a, 6));
+        assert_eq!(ranking[1], (f, 1));
+        assert!(ranking[2..].iter().all(|(_, count)| *count == 0));
+    }
+}
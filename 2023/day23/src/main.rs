@@ -1,3 +1,5 @@
+use std::env;
+
 use map::Map;
 
 mod map;
@@ -14,6 +16,16 @@ fn part_2(input: &str) -> usize {
 
 fn main() {
     let input = include_str!("../input.txt");
+
+    // Pass "draw" to see the longest hike for part 2 instead
+    if env::args().nth(1).as_deref() == Some("draw") {
+        let map = Map::new(input, false);
+        if let Some(route) = map.get_longest_route() {
+            print!("{}", map.render_route(&route));
+        }
+        return;
+    }
+
     println!("Part 1: {}", part_1(input));
     println!("Part 2: {}", part_2(input));
 }
@@ -28,4 +40,43 @@ mod tests {
         assert_eq!(part_1(example), 94);
         assert_eq!(part_2(example), 154);
     }
+
+    #[test]
+    fn test_route() {
+        let example = include_str!("../example.txt");
+        let map = Map::new(example, true);
+        let route = map.get_longest_route().unwrap();
+        assert_eq!(route.len(), 95);
+
+        // Every step is to a neighboring tile and no tile is visited twice
+        for pair in route.windows(2) {
+            assert_eq!(
+                pair[0].row.abs_diff(pair[1].row) + pair[0].col.abs_diff(pair[1].col),
+                1
+            );
+        }
+        let mut unique = route.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), route.len());
+
+        let rendered = map.render_route(&route);
+        assert_eq!(rendered.matches('O').count(), 95);
+        assert!(rendered.starts_with("#O#####################\n#OOOOOOO#########...###\n"));
+    }
+
+    #[test]
+    fn test_many_junctions() {
+        // A straight corridor with a dead end off to the side every other row, so there are more
+        // junctions than fit in a single word
+        let mut input = String::from("#.###\n");
+        for _ in 0..70 {
+            input += "#.###\n#..##\n";
+        }
+        input += "#.###\n";
+
+        let rows = input.lines().count();
+        assert_eq!(part_1(&input), rows - 1);
+        assert_eq!(part_2(&input), rows - 1);
+    }
 }
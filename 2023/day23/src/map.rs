@@ -1,4 +1,8 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
 pub struct Map {
     grid: Vec<Vec<char>>,
@@ -29,52 +33,43 @@ impl Map {
     }
 
     pub fn get_longest_path(&self) -> Option<usize> {
-        // Get the junction graph
-        let graph = self.get_junction_graph();
+        self.get_longest_route().map(|route| route.len() - 1)
+    }
 
-        // Exhaustive search over the junction graph to get the longest distance
-        let mut set = Vec::from([(self.start, HashMap::from([(self.start, 0)]))]);
-        let mut results = Vec::new();
+    /// Every tile along the longest hike, from the start to the end
+    pub fn get_longest_route(&self) -> Option<Vec<Coord>> {
+        let graph = Graph::new(self.start, self.end, self.get_junction_graph());
+        let junctions = graph.search()?;
 
-        while let Some((current, mut path)) = set.pop() {
-            if current == self.end {
-                // Add total distance to results
-                results.push(path.values().sum());
-                continue;
-            }
+        // Expand the junctions back out into tiles
+        let mut route = Vec::from([self.start]);
+        for pair in junctions.windows(2) {
+            route.extend(graph.tiles[&(pair[0], pair[1])].iter().copied());
+        }
 
-            // Get any neighbors that aren't on the path so far
-            let empty = HashMap::new();
-            let mut neighbors: Vec<_> = graph
-                .get(&current)
-                .unwrap_or(&empty)
-                .iter()
-                .filter(|(neighbor, _)| !path.contains_key(neighbor))
-                .collect();
+        Some(route)
+    }
 
-            // Clone path for any new paths
-            while neighbors.len() > 1 {
-                let (&neighbor, &distance) = neighbors.pop().unwrap();
-                let mut new_path = path.clone();
-                new_path.insert(neighbor, distance);
-                set.push((neighbor, new_path));
-            }
+    /// Draws the map with the route marked as `O`, like the puzzle description
+    pub fn render_route(&self, route: &[Coord]) -> String {
+        let route: HashSet<&Coord> = route.iter().collect();
+        let mut output = String::new();
 
-            // This should be the final one (if at all) so don't clone
-            if let Some((&neighbor, &distance)) = neighbors.pop() {
-                path.insert(neighbor, distance);
-                set.push((neighbor, path));
+        for (row, line) in self.grid.iter().enumerate() {
+            for (col, &tile) in line.iter().enumerate() {
+                if route.contains(&Coord { row, col }) {
+                    output.push('O');
+                } else {
+                    output.push(tile);
+                }
             }
+            output.push('\n');
         }
 
-        results.into_iter().max()
+        output
     }
 
-    fn get_neighbors(
-        &self,
-        position: &Coord,
-        path: &HashSet<Coord>,
-    ) -> Vec<(Coord, HashSet<Coord>)> {
+    fn get_neighbors(&self, position: &Coord, path: &HashSet<Coord>) -> Vec<(Coord, Vec<Coord>)> {
         [(-1, 0), (1, 0), (0, -1), (0, 1)]
             .into_iter()
             .filter_map(|(d_row, d_col)| self.get_relative(position, d_row, d_col, path))
@@ -87,7 +82,7 @@ impl Map {
         d_row: isize,
         d_col: isize,
         path: &HashSet<Coord>,
-    ) -> Option<(Coord, HashSet<Coord>)> {
+    ) -> Option<(Coord, Vec<Coord>)> {
         let row = position.row.checked_add_signed(d_row)?;
         let col = position.col.checked_add_signed(d_col)?;
         let &tile = self.grid.get(row)?.get(col)?;
@@ -101,7 +96,7 @@ impl Map {
             return if tile == '#' {
                 None
             } else {
-                Some((next, HashSet::from([next])))
+                Some((next, Vec::from([next])))
             };
         }
 
@@ -111,34 +106,37 @@ impl Map {
             '^' => self.get_relative(&next, -1, 0, path),
             '<' => self.get_relative(&next, 0, -1, path),
             'v' => self.get_relative(&next, 1, 0, path),
-            _ => Some((next, HashSet::new())),
+            _ => Some((next, Vec::new())),
         }?;
 
-        // If not newly inserted (returns false), that means it's invalid
-        if traveled.insert(next) {
-            Some((last, traveled))
-        } else {
+        // Sliding back onto this tile means it's invalid
+        if traveled.contains(&next) {
             None
+        } else {
+            traveled.insert(0, next);
+            Some((last, traveled))
         }
     }
 
-    /// Creates a graph between junctions (or spaces with more than 2 neighbors)
-    fn get_junction_graph(&self) -> HashMap<Coord, HashMap<Coord, usize>> {
-        let mut graph: HashMap<Coord, HashMap<Coord, usize>> = HashMap::new();
+    /// Creates a graph between junctions (or spaces with more than 2 neighbors), with the tiles
+    /// walked to get from one to the other
+    fn get_junction_graph(&self) -> HashMap<Coord, HashMap<Coord, Vec<Coord>>> {
+        let mut graph: HashMap<Coord, HashMap<Coord, Vec<Coord>>> = HashMap::new();
         let junctions = self.find_junctions();
         for &junction in &junctions {
             // Exhaustive search to nearby junctions
-            let mut queue = Vec::from([(junction, HashSet::from([junction]))]);
-            while let Some((current, mut path)) = queue.pop() {
+            let mut queue = Vec::from([(junction, HashSet::from([junction]), Vec::new())]);
+            while let Some((current, mut path, mut tiles)) = queue.pop() {
                 if current != junction && junctions.contains(&current) {
-                    // Found a path to another junction, update the graph
-                    let distance = path.len() - 1;
-                    graph
+                    // Found a path to another junction, update the graph with the longest
+                    let edge = graph
                         .entry(junction)
                         .or_default()
                         .entry(current)
-                        .and_modify(|e| *e = usize::max(*e, distance))
-                        .or_insert(distance);
+                        .or_default();
+                    if tiles.len() > edge.len() {
+                        *edge = tiles;
+                    }
                     continue;
                 }
 
@@ -148,14 +146,17 @@ impl Map {
                 while neighbors.len() > 1 {
                     let (neighbor, traveled) = neighbors.pop().unwrap();
                     let mut new_path = path.clone();
-                    new_path.extend(traveled);
-                    queue.push((neighbor, new_path));
+                    let mut new_tiles = tiles.clone();
+                    new_path.extend(traveled.iter().copied());
+                    new_tiles.extend(traveled);
+                    queue.push((neighbor, new_path, new_tiles));
                 }
 
                 // This should be the final one (if at all) so don't clone
                 if let Some((neighbor, traveled)) = neighbors.pop() {
-                    path.extend(traveled);
-                    queue.push((neighbor, path));
+                    path.extend(traveled.iter().copied());
+                    tiles.extend(traveled);
+                    queue.push((neighbor, path, tiles));
                 }
             }
         }
@@ -193,6 +194,223 @@ impl Map {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct Coord {
-    row: usize,
-    col: usize,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// The junction graph re-indexed to small ids, so a path can be tracked as bits in a [`Mask`]
+struct Graph {
+    /// Neighboring junction ids and the distance to each
+    edges: Vec<Vec<(usize, usize)>>,
+    /// The longest distance into each junction, for estimating how much further a path can go
+    longest_into: Vec<usize>,
+    /// Tiles walked along each edge, not including the junction it starts from
+    tiles: HashMap<(usize, usize), Vec<Coord>>,
+    start: usize,
+    end: usize,
+}
+
+/// A set of junction ids, as bits spread over as many words as there are junctions to track
+#[derive(Clone)]
+struct Mask(Vec<u64>);
+
+impl Mask {
+    /// A mask that fits ids up to `len`, holding just `id`
+    fn from(len: usize, id: usize) -> Self {
+        let mut mask = Mask(vec![0; len.div_ceil(u64::BITS as usize)]);
+        mask.insert(id);
+        mask
+    }
+
+    fn contains(&self, id: usize) -> bool {
+        self.0[id / u64::BITS as usize] & (1 << (id % u64::BITS as usize)) != 0
+    }
+
+    /// Returns whether the id wasn't already in the mask
+    fn insert(&mut self, id: usize) -> bool {
+        let is_new = !self.contains(id);
+        self.0[id / u64::BITS as usize] |= 1 << (id % u64::BITS as usize);
+        is_new
+    }
+
+    fn remove(&mut self, id: usize) {
+        self.0[id / u64::BITS as usize] &= !(1 << (id % u64::BITS as usize));
+    }
+}
+
+/// A path partway through the search
+#[derive(Clone)]
+struct Hike {
+    junction: usize,
+    visited: Mask,
+    distance: usize,
+    junctions: Vec<usize>,
+}
+
+impl Graph {
+    fn new(start: Coord, end: Coord, graph: HashMap<Coord, HashMap<Coord, Vec<Coord>>>) -> Self {
+        let mut coords: Vec<Coord> = graph
+            .iter()
+            .flat_map(|(from, to)| to.keys().chain([from]))
+            .copied()
+            .chain([start, end])
+            .collect();
+        coords.sort();
+        coords.dedup();
+
+        let ids: HashMap<Coord, usize> =
+            coords.iter().enumerate().map(|(id, &c)| (c, id)).collect();
+        let mut edges = vec![Vec::new(); coords.len()];
+        let mut longest_into = vec![0; coords.len()];
+        let mut tiles = HashMap::new();
+
+        for (from, to) in graph {
+            for (to, path) in to {
+                let (from, to) = (ids[&from], ids[&to]);
+                edges[from].push((to, path.len()));
+                longest_into[to] = longest_into[to].max(path.len());
+                tiles.insert((from, to), path);
+            }
+        }
+
+        Graph {
+            edges,
+            longest_into,
+            tiles,
+            start: ids[&start],
+            end: ids[&end],
+        }
+    }
+
+    /// Finds the junctions along the longest path, splitting the search across threads
+    fn search(&self) -> Option<Vec<usize>> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+        // Branch out until there's enough work to go around
+        let mut hikes = Vec::from([Hike {
+            junction: self.start,
+            visited: Mask::from(self.edges.len(), self.start),
+            distance: 0,
+            junctions: Vec::from([self.start]),
+        }]);
+        while hikes.len() < threads * 8 {
+            let branched: Vec<Hike> = hikes.iter().flat_map(|hike| self.branch(hike)).collect();
+            if branched.is_empty() || hikes.iter().all(|hike| hike.junction == self.end) {
+                break;
+            }
+
+            // Keep any finished hikes around as they can't branch any further
+            hikes.retain(|hike| hike.junction == self.end);
+            hikes.extend(branched);
+        }
+
+        let longest = AtomicUsize::new(0);
+        let results: Vec<Option<(usize, Vec<usize>)>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|i| {
+                    let hikes = &hikes;
+                    let longest = &longest;
+                    scope.spawn(move || {
+                        let mut best = None;
+                        for hike in hikes.iter().skip(i).step_by(threads) {
+                            self.search_from(&mut hike.clone(), longest, &mut best);
+                        }
+                        best
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        results
+            .into_iter()
+            .flatten()
+            .max_by_key(|(distance, _)| *distance)
+            .map(|(_, junctions)| junctions)
+    }
+
+    /// Depth first search, stepping the hike forward and back in place instead of cloning it
+    fn search_from(
+        &self,
+        hike: &mut Hike,
+        longest: &AtomicUsize,
+        best: &mut Option<(usize, Vec<usize>)>,
+    ) {
+        if hike.junction == self.end {
+            if best
+                .as_ref()
+                .is_none_or(|(distance, _)| hike.distance > *distance)
+            {
+                longest.fetch_max(hike.distance, Ordering::Relaxed);
+                *best = Some((hike.distance, hike.junctions.clone()));
+            }
+            return;
+        }
+
+        // Even visiting everything still reachable can't beat what's been found
+        let Some(remaining) = self.get_remaining(hike) else {
+            return;
+        };
+        if hike.distance + remaining <= longest.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let junction = hike.junction;
+        for &(next, distance) in self.edges[junction].iter() {
+            if hike.visited.contains(next) {
+                continue;
+            }
+
+            hike.junction = next;
+            hike.visited.insert(next);
+            hike.distance += distance;
+            hike.junctions.push(next);
+
+            self.search_from(hike, longest, best);
+
+            hike.junctions.pop();
+            hike.distance -= distance;
+            hike.visited.remove(next);
+            hike.junction = junction;
+        }
+    }
+
+    /// Most distance that could still be added, going into every unvisited junction that can
+    /// still be reached by its longest edge. Returns `None` if the end can't be reached at all.
+    fn get_remaining(&self, hike: &Hike) -> Option<usize> {
+        let mut reached = hike.visited.clone();
+        let mut stack = Vec::from([hike.junction]);
+        let mut remaining = 0;
+        while let Some(junction) = stack.pop() {
+            for &(next, _) in &self.edges[junction] {
+                if reached.insert(next) {
+                    remaining += self.longest_into[next];
+                    stack.push(next);
+                }
+            }
+        }
+
+        reached.contains(self.end).then_some(remaining)
+    }
+
+    /// Every step that can be taken to an unvisited junction. A hike that has reached the end is
+    /// finished, so it never goes any further.
+    fn branch<'a>(&'a self, hike: &'a Hike) -> impl Iterator<Item = Hike> + 'a {
+        self.edges[hike.junction]
+            .iter()
+            .filter(|_| hike.junction != self.end)
+            .filter(|(next, _)| !hike.visited.contains(*next))
+            .map(|&(next, distance)| {
+                let mut junctions = hike.junctions.clone();
+                junctions.push(next);
+                let mut visited = hike.visited.clone();
+                visited.insert(next);
+                Hike {
+                    junction: next,
+                    visited,
+                    distance: hike.distance + distance,
+                    junctions,
+                }
+            })
+    }
 }
@@ -0,0 +1,58 @@
+use crate::map::Tile;
+
+/// Where the guard stops when walking from each cell in each direction, so a walk can go from
+/// turn to turn without stepping through every cell in between.
+pub struct Jumps {
+    cols: usize,
+    /// The cell just before the next obstacle, or `None` if the guard walks off the map. Indexed
+    /// by cell then direction.
+    stops: Vec<Option<(usize, usize)>>,
+}
+
+impl Jumps {
+    pub fn new(grid: &[Vec<Tile>]) -> Self {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        let mut jumps = Jumps {
+            cols,
+            stops: vec![None; rows * cols * 4],
+        };
+
+        // Sweep each line starting from the edge the guard is walking towards, so the obstacle
+        // ahead of every cell has already been seen
+        for col in 0..cols {
+            jumps.fill(grid, 0, (0..rows).map(|row| (row, col)));
+            jumps.fill(grid, 2, (0..rows).rev().map(|row| (row, col)));
+        }
+        for row in 0..rows {
+            jumps.fill(grid, 1, (0..cols).rev().map(|col| (row, col)));
+            jumps.fill(grid, 3, (0..cols).map(|col| (row, col)));
+        }
+
+        jumps
+    }
+
+    pub fn get(&self, position: (usize, usize), direction: usize) -> Option<(usize, usize)> {
+        let (row, col) = position;
+        self.stops[(row * self.cols + col) * 4 + direction]
+    }
+
+    fn fill<I>(&mut self, grid: &[Vec<Tile>], direction: usize, line: I)
+    where
+        I: Iterator<Item = (usize, usize)>,
+    {
+        let mut stop = None;
+        let mut is_after_obstacle = false;
+        for (row, col) in line {
+            if grid[row][col] == Tile::Obstacle {
+                is_after_obstacle = true;
+                continue;
+            }
+            if is_after_obstacle {
+                stop = Some((row, col));
+                is_after_obstacle = false;
+            }
+            self.stops[(row * self.cols + col) * 4 + direction] = stop;
+        }
+    }
+}
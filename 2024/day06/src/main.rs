@@ -1,3 +1,4 @@
+mod jumps;
 mod map;
 mod tracker;
 
@@ -9,25 +10,12 @@ fn part_1(input: &str) -> usize {
 }
 
 fn part_2(input: &str) -> usize {
-    let mut map = map::Map::from(input);
-    let mut num_loops = 0;
-
-    for row in 0..map.grid.len() {
-        for col in 0..map.grid[row].len() {
-            if (row, col) == map.guard_coord || map.grid[row][col] == map::Tile::Obstacle {
-                continue;
-            }
-
-            map.grid[row][col] = map::Tile::Obstacle;
-            if map.is_guard_path_loop() {
-                num_loops += 1;
-            }
-            map.grid[row][col] = map::Tile::Empty;
-            map.reset();
-        }
-    }
+    get_loop_obstructions(input).len()
+}
 
-    num_loops
+fn get_loop_obstructions(input: &str) -> Vec<(usize, usize)> {
+    let mut map = map::Map::from(input);
+    map.get_loop_obstructions()
 }
 
 fn main() {
@@ -52,4 +40,11 @@ mod tests {
         let result = part_2(EXAMPLE);
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn test_example_obstructions() {
+        let mut result = get_loop_obstructions(EXAMPLE);
+        result.sort();
+        assert_eq!(result, vec![(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]);
+    }
 }
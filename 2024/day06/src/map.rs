@@ -1,6 +1,9 @@
-use crate::tracker;
+use crate::{jumps, tracker};
 
-#[derive(PartialEq)]
+/// Up, right, down and left, in the order the guard turns through them
+const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum Tile {
     Empty,
     Obstacle,
@@ -8,22 +11,14 @@ pub enum Tile {
 }
 
 pub struct Map {
-    pub grid: Vec<Vec<Tile>>,
-    pub guard_coord: (usize, usize),
-    guard_direction: (isize, isize),
-    original_guard_coord: (usize, usize),
-    tracker: tracker::Tracker,
+    grid: Vec<Vec<Tile>>,
+    guard_coord: (usize, usize),
+    guard_direction: usize,
 }
 
 impl Map {
-    pub fn reset(&mut self) {
-        self.guard_coord = self.original_guard_coord;
-        self.guard_direction = (-1, 0);
-        self.tracker = tracker::Tracker::new()
-    }
-
     pub fn get_guard_path_count(&mut self) -> usize {
-        while self.move_to_next_obstacle(true) {
+        while self.move_to_next_obstacle() {
             self.turn_guard_right();
         }
 
@@ -33,32 +28,81 @@ impl Map {
             .sum()
     }
 
-    pub fn is_guard_path_loop(&mut self) -> bool {
-        while self.move_to_next_obstacle(false) {
-            self.turn_guard_right();
+    /// Finds every cell where a new obstacle would trap the guard in a loop. Only cells on the
+    /// guard's path can change anything, so the guard walks it once and each cell is tried the
+    /// first time the guard is about to step onto it, carrying on the walk from there.
+    pub fn get_loop_obstructions(&mut self) -> Vec<(usize, usize)> {
+        let jumps = jumps::Jumps::new(&self.grid);
+        let mut tracker = tracker::Tracker::new(self.grid.len(), self.grid[0].len());
+        let mut obstructions = Vec::new();
 
-            if !self.tracker.mark(self.guard_coord, self.guard_direction) {
-                return true;
+        let (row, col) = self.guard_coord;
+        self.grid[row][col] = Tile::Path;
+        while let Some((next_tile, next_coord)) = self.get_next() {
+            match next_tile {
+                Tile::Obstacle => self.turn_guard_right(),
+                // The guard has already been here, so an obstacle would have stopped it earlier
+                Tile::Path => self.guard_coord = next_coord,
+                Tile::Empty => {
+                    if self.is_guard_path_loop(&jumps, &mut tracker, next_coord) {
+                        obstructions.push(next_coord);
+                    }
+                    let (row, col) = next_coord;
+                    self.grid[row][col] = Tile::Path;
+                    self.guard_coord = next_coord;
+                }
             }
         }
 
-        false
+        obstructions
     }
 
-    fn move_to_next_obstacle(&mut self, mark_path: bool) -> bool {
-        let mut is_obstacle = false;
+    /// Walks from the guard's current position with an extra obstacle, going from turn to turn
+    fn is_guard_path_loop(
+        &self,
+        jumps: &jumps::Jumps,
+        tracker: &mut tracker::Tracker,
+        obstruction: (usize, usize),
+    ) -> bool {
+        tracker.clear();
+        let mut coord = self.guard_coord;
+        let mut direction = self.guard_direction;
 
         loop {
-            if mark_path {
-                let (row, col) = self.guard_coord;
-                self.grid[row][col] = Tile::Path;
+            let stop = jumps.get(coord, direction);
+            // The new obstacle cuts the jump short when it's closer than the next one
+            let stop = match get_stop_before(coord, direction, obstruction) {
+                Some(blocked)
+                    if stop.is_none_or(|stop| distance(coord, blocked) < distance(coord, stop)) =>
+                {
+                    Some(blocked)
+                }
+                _ => stop,
+            };
+            let Some(stop) = stop else {
+                return false;
+            };
+
+            coord = stop;
+            direction = (direction + 1) % DIRECTIONS.len();
+            if !tracker.mark(coord, direction) {
+                return true;
             }
+        }
+    }
+
+    fn move_to_next_obstacle(&mut self) -> bool {
+        let mut is_obstacle = false;
+
+        loop {
+            let (row, col) = self.guard_coord;
+            self.grid[row][col] = Tile::Path;
 
             let Some((next_tile, next_coord)) = self.get_next() else {
                 break;
             };
 
-            if *next_tile == Tile::Obstacle {
+            if next_tile == Tile::Obstacle {
                 is_obstacle = true;
                 break;
             }
@@ -69,24 +113,18 @@ impl Map {
         is_obstacle
     }
 
-    fn get_next(&self) -> Option<(&Tile, (usize, usize))> {
+    fn get_next(&self) -> Option<(Tile, (usize, usize))> {
         let (row, col) = self.guard_coord;
-        let (d_row, d_col) = self.guard_direction;
+        let (d_row, d_col) = DIRECTIONS[self.guard_direction];
         let new_row = row.checked_add_signed(d_row)?;
         let new_col = col.checked_add_signed(d_col)?;
         let tile = self.grid.get(new_row)?.get(new_col)?;
 
-        Some((tile, (new_row, new_col)))
+        Some((*tile, (new_row, new_col)))
     }
 
     fn turn_guard_right(&mut self) {
-        self.guard_direction = match self.guard_direction {
-            (-1, 0) => (0, 1),
-            (0, 1) => (1, 0),
-            (1, 0) => (0, -1),
-            (0, -1) => (-1, 0),
-            (d_row, d_col) => (d_row, d_col),
-        };
+        self.guard_direction = (self.guard_direction + 1) % DIRECTIONS.len();
     }
 }
 
@@ -115,9 +153,28 @@ impl From<&str> for Map {
         Map {
             grid,
             guard_coord,
-            guard_direction: (-1, 0),
-            original_guard_coord: guard_coord,
-            tracker: tracker::Tracker::new(),
+            guard_direction: 0,
         }
     }
 }
+
+/// The cell just before an obstacle, if it's straight ahead when walking from a cell
+fn get_stop_before(
+    coord: (usize, usize),
+    direction: usize,
+    obstacle: (usize, usize),
+) -> Option<(usize, usize)> {
+    let (row, col) = coord;
+    let (o_row, o_col) = obstacle;
+    match direction {
+        0 if col == o_col && o_row < row => Some((o_row + 1, col)),
+        1 if row == o_row && o_col > col => Some((row, o_col - 1)),
+        2 if col == o_col && o_row > row => Some((o_row - 1, col)),
+        3 if row == o_row && o_col < col => Some((row, o_col + 1)),
+        _ => None,
+    }
+}
+
+fn distance(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
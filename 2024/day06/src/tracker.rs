@@ -1,33 +1,38 @@
-use std::collections::{HashMap, HashSet};
-
+/// Every (position, direction) the guard has turned at, packed into one bit each so it can be
+/// cleared and reused for each walk.
 pub struct Tracker {
-    positions: HashMap<(usize, usize), HashSet<(isize, isize)>>,
+    cols: usize,
+    bits: Vec<u64>,
 }
 impl Tracker {
-    pub fn new() -> Self {
+    pub fn new(rows: usize, cols: usize) -> Self {
         Tracker {
-            positions: HashMap::new(),
+            cols,
+            bits: vec![0; (rows * cols * 4).div_ceil(64)],
         }
     }
 
-    /// Marks a position with a given direction. If the position already exists,
-    /// the direction is added to the set of directions for that position.
+    /// Marks a position with a given direction.
     ///
     /// # Arguments
     ///
     /// * `position` - A tuple representing the (row, col) coordinates of the position.
-    /// * `direction` - A tuple representing the (d_row, d_col) direction to mark at the position.
+    /// * `direction` - The index of the direction to mark at the position.
     ///
     /// # Returns
     ///
     /// * `true` if the position and direction was newly inserted.
     /// * `false` if the position and direction already existed.
-    pub fn mark(&mut self, position: (usize, usize), direction: (isize, isize)) -> bool {
-        if let Some(directions) = self.positions.get_mut(&position) {
-            directions.insert(direction)
-        } else {
-            self.positions.insert(position, HashSet::from([direction]));
-            true
-        }
+    pub fn mark(&mut self, position: (usize, usize), direction: usize) -> bool {
+        let (row, col) = position;
+        let index = (row * self.cols + col) * 4 + direction;
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        let is_new = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        is_new
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
     }
 }
@@ -1,13 +1,13 @@
-use std::slice::Iter;
+use crate::operator::{Left, Operator};
 
-pub struct Equation {
+pub struct Equation<'a> {
     pub result: u128,
     numbers: Vec<u128>,
-    with_concat: bool,
+    operators: &'a [Operator],
 }
 
-impl Equation {
-    pub fn init(s: &str, with_concat: bool) -> Option<Self> {
+impl<'a> Equation<'a> {
+    pub fn init(s: &str, operators: &'a [Operator]) -> Option<Self> {
         let (result, numbers) = s.split_once(":")?;
 
         let result = parse_u128(result)?;
@@ -16,32 +16,131 @@ impl Equation {
             .split(' ')
             .map(parse_u128)
             .collect::<Option<Vec<u128>>>()?;
+        if numbers.is_empty() {
+            return None;
+        }
 
         Some(Equation {
             result,
             numbers,
-            with_concat,
+            operators,
         })
     }
 
     pub fn is_possible(&self) -> bool {
-        self.check_possible(0, self.numbers.iter())
+        let last = self.numbers.len() - 1;
+        self.search(self.result, last, &mut Vec::new(), &mut |_| true)
     }
 
-    fn check_possible(&self, sum: u128, mut numbers: Iter<'_, u128>) -> bool {
-        let Some(next) = numbers.next() else {
-            return self.result == sum;
-        };
+    /// Finds every way of putting operators between the numbers that gives the result
+    pub fn get_solutions(&self) -> Vec<Vec<Operator>> {
+        let mut solutions = Vec::new();
+        let last = self.numbers.len() - 1;
+        self.search(self.result, last, &mut Vec::new(), &mut |solution| {
+            solutions.push(solution.to_vec());
+            false
+        });
+        solutions
+    }
+
+    /// Writes the numbers out with the operators between them, e.g. `81 + 40 * 27 = 3267`
+    pub fn to_expression(&self, operators: &[Operator]) -> String {
+        let mut expression = self.numbers[0].to_string();
+        let mut value = Some(self.numbers[0]);
+        for (operator, number) in operators.iter().zip(&self.numbers[1..]) {
+            expression += &format!(" {} {number}", operator.symbol);
+            value = value.and_then(|value| (operator.apply)(value, *number));
+        }
+
+        match value {
+            Some(value) => format!("{expression} = {value}"),
+            None => format!("{expression} = ?"),
+        }
+    }
 
-        if self.with_concat && self.check_possible(concat(&sum, next), numbers.clone()) {
-            return true;
+    /// Works back from the right, undoing an operator on the last number left each time. Only
+    /// operators that can be undone get tried, e.g. `*` only when the target is divisible by the
+    /// number, so most branches end straight away. `chosen` holds the operators picked so far,
+    /// from right to left. Calls `on_solution` with each solution, from left to right, and stops
+    /// once it returns true.
+    fn search<F>(
+        &self,
+        target: u128,
+        index: usize,
+        chosen: &mut Vec<Operator>,
+        on_solution: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&[Operator]) -> bool,
+    {
+        if index == 0 {
+            let solution: Vec<Operator> = chosen.iter().rev().copied().collect();
+            return self.numbers[0] == target && on_solution(&solution);
         }
 
-        if self.check_possible(sum + next, numbers.clone()) {
-            return true;
+        for operator in self.operators {
+            let is_done = match (operator.undo)(target, self.numbers[index]) {
+                None => false,
+                Some(Left::Exactly(left)) => {
+                    chosen.push(*operator);
+                    let is_done = self.search(left, index - 1, chosen, on_solution);
+                    chosen.pop();
+                    is_done
+                }
+                // The numbers before only have to work out to something, whatever it is
+                Some(Left::Anything) => {
+                    chosen.push(*operator);
+                    let suffix: Vec<Operator> = chosen.iter().rev().copied().collect();
+                    chosen.pop();
+                    self.search_forward(
+                        self.numbers[0],
+                        0,
+                        index - 1,
+                        &mut Vec::new(),
+                        &mut |prefix| on_solution(&[prefix, &suffix].concat()),
+                    )
+                }
+            };
+            if is_done {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Tries every way of putting operators between the numbers up to `last` that can be worked
+    /// out at all, going from the left. Calls `on_prefix` with the operators for each, and stops
+    /// once it returns true.
+    fn search_forward<F>(
+        &self,
+        value: u128,
+        index: usize,
+        last: usize,
+        prefix: &mut Vec<Operator>,
+        on_prefix: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&[Operator]) -> bool,
+    {
+        if index == last {
+            return on_prefix(prefix);
+        }
+
+        for operator in self.operators {
+            let Some(next) = (operator.apply)(value, self.numbers[index + 1]) else {
+                continue;
+            };
+
+            prefix.push(*operator);
+            let is_done = self.search_forward(next, index + 1, last, prefix, on_prefix);
+            prefix.pop();
+            if is_done {
+                return true;
+            }
         }
 
-        self.check_possible(sum * next, numbers)
+        false
     }
 }
 
@@ -49,6 +148,99 @@ fn parse_u128(s: &str) -> Option<u128> {
     s.parse().ok()
 }
 
-fn concat(a: &u128, b: &u128) -> u128 {
-    (a.to_string() + &b.to_string()).parse().unwrap()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{ADD, CONCAT, DIVIDE, MULTIPLY, POWER, SUBTRACT};
+
+    fn get_expressions(s: &str, operators: &[Operator]) -> Vec<String> {
+        let equation = Equation::init(s, operators).unwrap();
+        let mut expressions: Vec<String> = equation
+            .get_solutions()
+            .iter()
+            .map(|solution| equation.to_expression(solution))
+            .collect();
+        expressions.sort();
+        expressions
+    }
+
+    #[test]
+    fn test_solutions() {
+        assert_eq!(
+            vec!["81 * 40 + 27 = 3267", "81 + 40 * 27 = 3267"],
+            get_expressions("3267: 81 40 27", &[ADD, MULTIPLY])
+        );
+        assert_eq!(
+            vec!["6 * 8 || 6 * 15 = 7290"],
+            get_expressions("7290: 6 8 6 15", &[ADD, MULTIPLY, CONCAT])
+        );
+        assert!(get_expressions("83: 17 5", &[ADD, MULTIPLY]).is_empty());
+    }
+
+    #[test]
+    fn test_other_operators() {
+        let operators = [ADD, SUBTRACT, MULTIPLY, DIVIDE, POWER];
+        assert_eq!(
+            vec![
+                "3 * 2 * 1 - 4 = 2",
+                "3 * 2 / 1 - 4 = 2",
+                "3 * 2 ^ 1 - 4 = 2",
+                "3 + 2 + 1 - 4 = 2",
+                "3 ^ 2 - 1 / 4 = 2",
+            ],
+            get_expressions("2: 3 2 1 4", &operators)
+        );
+        assert_eq!(
+            vec!["2 ^ 10 - 24 = 1000"],
+            get_expressions("1000: 2 10 24", &operators)
+        );
+    }
+
+    #[test]
+    fn test_zero_right() {
+        assert_eq!(
+            vec!["5 * 0 = 0"],
+            get_expressions("0: 5 0", &[ADD, MULTIPLY])
+        );
+        assert_eq!(vec!["7 ^ 0 = 1"], get_expressions("1: 7 0", &[ADD, POWER]));
+        assert!(Equation::init("0: 5 0", &[ADD, MULTIPLY])
+            .unwrap()
+            .is_possible());
+        assert!(Equation::init("1: 7 0", &[POWER]).unwrap().is_possible());
+
+        // Anything before the zero goes, as long as it can be worked out
+        assert_eq!(
+            vec!["3 * 4 * 0 = 0", "3 + 4 * 0 = 0"],
+            get_expressions("0: 3 4 0", &[ADD, MULTIPLY])
+        );
+        assert_eq!(
+            vec!["2 * 3 * 0 = 0"],
+            get_expressions("0: 2 3 0", &[SUBTRACT, MULTIPLY])
+        );
+        assert_eq!(
+            vec!["3 * 0 + 2 = 2"],
+            get_expressions("2: 3 0 2", &[ADD, MULTIPLY])
+        );
+    }
+
+    #[test]
+    fn test_init() {
+        assert!(Equation::init("5: ", &[ADD]).is_none());
+        assert!(Equation::init("5:", &[ADD]).is_none());
+        assert!(Equation::init("5 1 2", &[ADD]).is_none());
+        assert!(Equation::init("5: 5", &[ADD]).unwrap().is_possible());
+    }
+
+    #[test]
+    fn test_custom_operator() {
+        let xor = Operator {
+            symbol: "xor",
+            apply: |left, right| Some(left ^ right),
+            undo: |result, right| Some(Left::Exactly(result ^ right)),
+        };
+        assert_eq!(
+            vec!["5 xor 3 + 2 = 8"],
+            get_expressions("8: 5 3 2", &[ADD, xor])
+        );
+    }
 }
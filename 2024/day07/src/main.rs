@@ -1,11 +1,16 @@
+use std::env;
+
+use operator::{Operator, ADD, CONCAT, MULTIPLY};
+
 mod equation;
+mod operator;
 
 const INPUT: &str = include_str!("input.txt");
 
 fn part_1(input: &str) -> u128 {
     input
         .lines()
-        .filter_map(|line| equation::Equation::init(line, false))
+        .filter_map(|line| equation::Equation::init(line, &[ADD, MULTIPLY]))
         .filter(|eq| eq.is_possible())
         .map(|eq| eq.result)
         .sum()
@@ -14,13 +19,46 @@ fn part_1(input: &str) -> u128 {
 fn part_2(input: &str) -> u128 {
     input
         .lines()
-        .filter_map(|line| equation::Equation::init(line, true))
+        .filter_map(|line| equation::Equation::init(line, &[ADD, MULTIPLY, CONCAT]))
         .filter(|eq| eq.is_possible())
         .map(|eq| eq.result)
         .sum()
 }
 
+/// Every way each equation can be made true, one expression per line
+fn explain(input: &str, operators: &[Operator]) -> String {
+    let mut output = String::new();
+    for equation in input
+        .lines()
+        .filter_map(|line| equation::Equation::init(line, operators))
+    {
+        for solution in equation.get_solutions() {
+            output += &equation.to_expression(&solution);
+            output += "\n";
+        }
+    }
+    output
+}
+
 fn main() {
+    // Optionally takes the symbols of the operators to use, e.g. `explain + - ||`
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|s| s.as_str()) == Some("explain") {
+        let operators = if args.len() > 2 {
+            args[2..]
+                .iter()
+                .map(|symbol| operator::find(symbol).ok_or(format!("Unknown operator {symbol}")))
+                .collect()
+        } else {
+            Ok(vec![ADD, MULTIPLY, CONCAT])
+        };
+        match operators {
+            Ok(operators) => print!("{}", explain(INPUT, &operators)),
+            Err(error) => println!("Explain: {error}"),
+        }
+        return;
+    }
+
     println!("Part 1: {:?}", part_1(INPUT));
     println!("Part 2: {:?}", part_2(INPUT));
 }
@@ -0,0 +1,135 @@
+/// An operator that can go between two numbers. Equations are always worked out left to right,
+/// so solving one means undoing the operators from the right.
+#[derive(Clone, Copy)]
+pub struct Operator {
+    pub symbol: &'static str,
+    /// Works out `left op right`, or `None` when it isn't defined or overflows
+    pub apply: fn(u128, u128) -> Option<u128>,
+    /// Works out the left side from the result and the right side, or `None` when no left side
+    /// gives the result
+    pub undo: fn(u128, u128) -> Option<Left>,
+}
+
+/// What the left side of an operator has to be to give a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Left {
+    Exactly(u128),
+    /// Any left side gives the result, e.g. when multiplying by zero
+    Anything,
+}
+
+pub const ADD: Operator = Operator {
+    symbol: "+",
+    apply: |left, right| left.checked_add(right),
+    undo: |result, right| result.checked_sub(right).map(Left::Exactly),
+};
+
+pub const MULTIPLY: Operator = Operator {
+    symbol: "*",
+    apply: |left, right| left.checked_mul(right),
+    undo: |result, right| match right {
+        0 => (result == 0).then_some(Left::Anything),
+        _ => (result % right == 0).then_some(Left::Exactly(result / right)),
+    },
+};
+
+/// Joins the digits of both sides, e.g. `12 || 345` is `12345`
+pub const CONCAT: Operator = Operator {
+    symbol: "||",
+    apply: |left, right| left.checked_mul(get_shift(right)?)?.checked_add(right),
+    undo: |result, right| {
+        let shift = get_shift(right)?;
+        (result % shift == right).then_some(Left::Exactly(result / shift))
+    },
+};
+
+pub const SUBTRACT: Operator = Operator {
+    symbol: "-",
+    apply: |left, right| left.checked_sub(right),
+    undo: |result, right| result.checked_add(right).map(Left::Exactly),
+};
+
+/// Only divides exactly, so there's never a remainder to lose
+pub const DIVIDE: Operator = Operator {
+    symbol: "/",
+    apply: |left, right| (right != 0 && left % right == 0).then(|| left / right),
+    undo: |result, right| {
+        result
+            .checked_mul(right)
+            .filter(|_| right != 0)
+            .map(Left::Exactly)
+    },
+};
+
+pub const POWER: Operator = Operator {
+    symbol: "^",
+    apply: |left, right| left.checked_pow(u32::try_from(right).ok()?),
+    undo: |result, right| match u32::try_from(right).ok()? {
+        0 => (result == 1).then_some(Left::Anything),
+        exponent => get_root(result, exponent).map(Left::Exactly),
+    },
+};
+
+/// Every operator defined here
+pub const ALL: [Operator; 6] = [ADD, MULTIPLY, CONCAT, SUBTRACT, DIVIDE, POWER];
+
+pub fn find(symbol: &str) -> Option<Operator> {
+    ALL.into_iter().find(|operator| operator.symbol == symbol)
+}
+
+/// The power of ten that moves a number left by as many digits as `right` has
+fn get_shift(right: u128) -> Option<u128> {
+    10u128.checked_pow(right.checked_ilog10().unwrap_or(0) + 1)
+}
+
+/// The whole number that gives `result` when raised to `exponent`, if there is one
+fn get_root(result: u128, exponent: u32) -> Option<u128> {
+    let (mut low, mut high) = (0, result);
+    while low <= high {
+        let middle = low + (high - low) / 2;
+        match middle.checked_pow(exponent) {
+            Some(power) if power == result => return Some(middle),
+            Some(power) if power < result => low = middle + 1,
+            _ => high = middle.checked_sub(1)?,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo() {
+        for operator in ALL {
+            for (left, right) in [(12, 3), (1, 1), (7, 10), (0, 5), (100, 2)] {
+                if let Some(result) = (operator.apply)(left, right) {
+                    assert_eq!(
+                        Some(Left::Exactly(left)),
+                        (operator.undo)(result, right),
+                        "{left} {} {right}",
+                        operator.symbol
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_undo_anything() {
+        assert_eq!(Some(Left::Anything), (MULTIPLY.undo)(0, 0));
+        assert_eq!(Some(Left::Anything), (POWER.undo)(1, 0));
+    }
+
+    #[test]
+    fn test_no_undo() {
+        assert_eq!(None, (MULTIPLY.undo)(10, 3));
+        assert_eq!(None, (MULTIPLY.undo)(3, 0));
+        assert_eq!(None, (POWER.undo)(3, 0));
+        assert_eq!(None, (CONCAT.undo)(1234, 5));
+        assert_eq!(None, (POWER.undo)(10, 2));
+        assert_eq!(None, (ADD.undo)(2, 3));
+    }
+}
@@ -1,3 +1,25 @@
+use std::str::FromStr;
+
+/// What pressing each button costs, and how many times it can be pressed at most
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    pub cost_a: usize,
+    pub cost_b: usize,
+    pub limit_a: Option<usize>,
+    pub limit_b: Option<usize>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            cost_a: 3,
+            cost_b: 1,
+            limit_a: None,
+            limit_b: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ClawMachine {
     button_a: Coord,
@@ -6,63 +28,238 @@ pub struct ClawMachine {
 }
 
 impl ClawMachine {
-    pub fn get_minimum_tokens(&self) -> Option<usize> {
-        let main_determinant =
-            self.button_a.x * self.button_b.y - self.button_a.y * self.button_b.x;
-        let determinant_a = self.prize.x * self.button_b.y - self.prize.y * self.button_b.x;
-        let determinant_b = self.prize.x * self.button_a.y - self.prize.y * self.button_a.x;
+    pub fn get_minimum_tokens(&self, rules: &Rules) -> Option<usize> {
+        let (a_presses, b_presses) = self.get_presses(rules)?;
+        Some(a_presses * rules.cost_a + b_presses * rules.cost_b)
+    }
+
+    /// Finds the cheapest number of presses of each button that lands on the prize
+    pub fn get_presses(&self, rules: &Rules) -> Option<(usize, usize)> {
+        let (a, b, prize) = (self.button_a, self.button_b, self.prize);
+        let main_determinant = a.x * b.y - a.y * b.x;
 
-        if determinant_a % main_determinant != 0 || determinant_b % main_determinant != 0 {
+        let (a_presses, b_presses) = if main_determinant != 0 {
+            // Only one way to reach the prize, so it's Cramer's rule
+            let determinant_a = prize.x * b.y - prize.y * b.x;
+            let determinant_b = a.x * prize.y - a.y * prize.x;
+            if determinant_a % main_determinant != 0 || determinant_b % main_determinant != 0 {
+                return None;
+            }
+            (
+                determinant_a / main_determinant,
+                determinant_b / main_determinant,
+            )
+        } else {
+            self.get_collinear_presses(rules)?
+        };
+
+        let is_within = |presses: i128, limit: Option<usize>| {
+            presses >= 0 && limit.is_none_or(|limit| presses <= limit as i128)
+        };
+        if !is_within(a_presses, rules.limit_a) || !is_within(b_presses, rules.limit_b) {
             return None;
         }
 
-        let a_presses = (determinant_a / main_determinant).unsigned_abs();
-        let b_presses = (determinant_b / main_determinant).unsigned_abs();
-
-        Some(a_presses * 3 + b_presses)
+        Some((a_presses as usize, b_presses as usize))
     }
 
     pub fn move_prize_position(&mut self, d_x: isize, d_y: isize) {
-        self.prize.x += d_x;
-        self.prize.y += d_y;
+        self.prize.x += d_x as i128;
+        self.prize.y += d_y as i128;
+    }
+
+    /// When both buttons move along the same line, the prize has to be on that line too, and
+    /// then it's one equation `a * A + b * B = P` with lots of solutions to pick the cheapest from
+    fn get_collinear_presses(&self, rules: &Rules) -> Option<(i128, i128)> {
+        let (a, b, prize) = (self.button_a, self.button_b, self.prize);
+
+        // Any button that moves at all gives the line, otherwise the claw can't leave the start
+        let Some(line) = [a, b].into_iter().find(|button| *button != Coord::ZERO) else {
+            return (prize == Coord::ZERO).then_some((0, 0));
+        };
+        if line.x * prize.y - line.y * prize.x != 0 {
+            return None;
+        }
+
+        // Every move is a multiple of the line, so one axis where it moves says it all
+        let (step_a, step_b, target) = if line.x != 0 {
+            (a.x, b.x, prize.x)
+        } else {
+            (a.y, b.y, prize.y)
+        };
+
+        // A button that doesn't move is never worth pressing
+        if step_a == 0 {
+            return (target % step_b == 0).then(|| (0, target / step_b));
+        }
+        if step_b == 0 {
+            return (target % step_a == 0).then(|| (target / step_a, 0));
+        }
+
+        let (g, x, y) = extended_gcd(step_a, step_b);
+        if target % g != 0 {
+            return None;
+        }
+
+        // Every solution is (a0 + k * d_a, b0 + k * d_b) for some k
+        let (a0, b0) = (x * (target / g), y * (target / g));
+        let (d_a, d_b) = (step_b / g, -step_a / g);
+        let mut range = (None, None);
+        narrow(&mut range, a0, d_a, rules.limit_a);
+        narrow(&mut range, b0, d_b, rules.limit_b);
+
+        // The cost changes by the same amount for each step of k, so the cheapest is at one end.
+        // When every solution costs the same, the one with the fewest presses wins.
+        let slope = rules.cost_a as i128 * d_a + rules.cost_b as i128 * d_b;
+        let presses_slope = d_a + d_b;
+        let k = match range {
+            (Some(min), Some(max)) if min > max => return None,
+            (Some(min), Some(max)) => {
+                if slope > 0 || (slope == 0 && presses_slope > 0) {
+                    min
+                } else {
+                    max
+                }
+            }
+            (Some(k), None) | (None, Some(k)) => k,
+            (None, None) => 0,
+        };
+
+        Some((a0 + k * d_a, b0 + k * d_b))
     }
 }
 
-impl From<&str> for ClawMachine {
-    fn from(s: &str) -> Self {
-        let mut lines = s.lines();
+impl FromStr for ClawMachine {
+    type Err = String;
 
-        // This is hilariously brittle but who cares it's AoC
-        ClawMachine {
-            button_a: lines.next().unwrap().into(),
-            button_b: lines.next().unwrap().into(),
-            prize: lines.next().unwrap().into(),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut next = |label: &str, sign: char| -> Result<Coord, Self::Err> {
+            let line = lines
+                .next()
+                .ok_or_else(|| format!("Missing line for {label}"))?;
+            let coords = line
+                .strip_prefix(label)
+                .and_then(|line| line.strip_prefix(':'))
+                .ok_or_else(|| format!("Expected {label} but found: {line}"))?;
+            Coord::parse(coords, sign)
+        };
+
+        let machine = ClawMachine {
+            button_a: next("Button A", '+')?,
+            button_b: next("Button B", '+')?,
+            prize: next("Prize", '=')?,
+        };
+
+        if let Some(line) = lines.next() {
+            return Err(format!("Unexpected line after the prize: {line}"));
         }
+
+        Ok(machine)
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Coord {
-    x: isize,
-    y: isize,
+    x: i128,
+    y: i128,
 }
 
-impl From<&str> for Coord {
-    fn from(s: &str) -> Self {
-        let index = s.find('X').unwrap();
-        let (x_str, y_str) = s[index..].split_once(',').unwrap();
+impl Coord {
+    const ZERO: Coord = Coord { x: 0, y: 0 };
 
-        Coord {
-            x: x_str.trim()[2..].parse().unwrap(),
-            y: y_str.trim()[2..].parse().unwrap(),
-        }
+    /// Reads coordinates like `X+94, Y+34`, where the `+` can be any separator
+    fn parse(s: &str, separator: char) -> Result<Self, String> {
+        let (x_str, y_str) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Expected X and Y in: {s}"))?;
+
+        let parse_axis = |axis_str: &str, axis: char| {
+            axis_str
+                .trim()
+                .strip_prefix(axis)
+                .and_then(|value| value.strip_prefix(separator))
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("Expected {axis}{separator} and a number in: {s}"))
+        };
+
+        Ok(Coord {
+            x: parse_axis(x_str, 'X')?,
+            y: parse_axis(y_str, 'Y')?,
+        })
     }
 }
 
+/// Finds the gcd of two numbers, along with `x` and `y` such that `a * x + b * y = gcd`. The gcd
+/// always comes out positive.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Narrows the range of `k` so that `base + k * step` is at least zero and at most the limit
+fn narrow(range: &mut (Option<i128>, Option<i128>), base: i128, step: i128, limit: Option<usize>) {
+    let limit = limit.map(|limit| limit as i128);
+
+    // Flipping the sign of k when the step is negative gives the same bounds the other way round
+    let (min, max) = if step > 0 {
+        (
+            Some(ceil_div(-base, step)),
+            limit.map(|limit| floor_div(limit - base, step)),
+        )
+    } else {
+        (
+            limit.map(|limit| ceil_div(base - limit, -step)),
+            Some(floor_div(base, -step)),
+        )
+    };
+
+    range.0 = match (range.0, min) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    range.1 = match (range.1, max) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+}
+
+fn floor_div(a: i128, b: i128) -> i128 {
+    a.div_euclid(b)
+}
+
+fn ceil_div(a: i128, b: i128) -> i128 {
+    -(-a).div_euclid(b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn machine(a: (i128, i128), b: (i128, i128), prize: (i128, i128)) -> ClawMachine {
+        ClawMachine {
+            button_a: Coord { x: a.0, y: a.1 },
+            button_b: Coord { x: b.0, y: b.1 },
+            prize: Coord {
+                x: prize.0,
+                y: prize.1,
+            },
+        }
+    }
+
     #[test]
     fn test_parse() {
         let machine: ClawMachine = "\
@@ -70,9 +267,106 @@ Button A: X+94, Y+34
 Button B: X+22, Y+67
 Prize: X=8400, Y=5400
 "
-        .into();
+        .parse()
+        .unwrap();
         assert_eq!(machine.button_a, Coord { x: 94, y: 34 });
         assert_eq!(machine.button_b, Coord { x: 22, y: 67 });
         assert_eq!(machine.prize, Coord { x: 8400, y: 5400 });
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("Button A: X+94, Y+34\nButton B: X+22, Y+67"
+            .parse::<ClawMachine>()
+            .is_err());
+        assert!(
+            "Button B: X+94, Y+34\nButton A: X+22, Y+67\nPrize: X=8400, Y=5400"
+                .parse::<ClawMachine>()
+                .is_err()
+        );
+        assert!(
+            "Button A: X+94 Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400"
+                .parse::<ClawMachine>()
+                .is_err()
+        );
+        assert!(
+            "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X+8400, Y+5400"
+                .parse::<ClawMachine>()
+                .is_err()
+        );
+        assert!(
+            "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\nPrize: X=1, Y=1"
+                .parse::<ClawMachine>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_negative_presses() {
+        // Cramer's rule gives -1 presses of B here, which isn't possible
+        let machine = machine((1, 0), (1, 1), (2, -1));
+        assert_eq!(None, machine.get_presses(&Rules::default()));
+    }
+
+    #[test]
+    fn test_limits() {
+        let machine = machine((94, 34), (22, 67), (8400, 5400));
+        let rules = Rules {
+            limit_a: Some(79),
+            ..Rules::default()
+        };
+        assert_eq!(Some((80, 40)), machine.get_presses(&Rules::default()));
+        assert_eq!(None, machine.get_presses(&rules));
+    }
+
+    #[test]
+    fn test_collinear() {
+        // A moves three times as far as B for three times the cost, so it's down to the fewest
+        // presses
+        let claw = machine((3, 6), (1, 2), (10, 20));
+        assert_eq!(Some((3, 1)), claw.get_presses(&Rules::default()));
+        assert_eq!(Some(10), claw.get_minimum_tokens(&Rules::default()));
+
+        // B is the cheaper way to go when A costs more
+        let rules = Rules {
+            cost_a: 4,
+            ..Rules::default()
+        };
+        assert_eq!(Some((0, 10)), claw.get_presses(&rules));
+
+        // Unless it can only be pressed so many times
+        let rules = Rules {
+            limit_b: Some(4),
+            ..rules
+        };
+        assert_eq!(Some((2, 4)), claw.get_presses(&rules));
+
+        // Neither button reaches the prize on its own
+        let claw = machine((4, 2), (6, 3), (10, 5));
+        assert_eq!(Some((1, 1)), claw.get_presses(&Rules::default()));
+        let claw = machine((4, 2), (6, 3), (5, 5));
+        assert_eq!(None, claw.get_presses(&Rules::default()));
+        let claw = machine((4, 2), (6, 3), (2, 1));
+        assert_eq!(None, claw.get_presses(&Rules::default()));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let rules = Rules::default();
+        assert_eq!(
+            Some((0, 5)),
+            machine((0, 0), (2, 3), (10, 15)).get_presses(&rules)
+        );
+        assert_eq!(
+            Some((0, 0)),
+            machine((0, 0), (0, 0), (0, 0)).get_presses(&rules)
+        );
+        assert_eq!(None, machine((0, 0), (0, 0), (1, 0)).get_presses(&rules));
+
+        // Going backwards and forwards along the same line
+        assert_eq!(
+            Some((3, 1)),
+            machine((2, 2), (-3, -3), (3, 3)).get_presses(&rules)
+        );
+    }
 }
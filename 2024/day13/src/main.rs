@@ -3,10 +3,16 @@ mod claw_machine;
 const INPUT: &str = include_str!("../input.txt");
 
 fn part_1(input: &str) -> usize {
+    // Each button is pressed at most 100 times to win a prize
+    let rules = claw_machine::Rules {
+        limit_a: Some(100),
+        limit_b: Some(100),
+        ..Default::default()
+    };
     input
         .split("\n\n")
-        .map(claw_machine::ClawMachine::from)
-        .filter_map(|machine| machine.get_minimum_tokens())
+        .map(|s| s.parse::<claw_machine::ClawMachine>().unwrap())
+        .filter_map(|machine| machine.get_minimum_tokens(&rules))
         .sum()
 }
 
@@ -14,11 +20,11 @@ fn part_2(input: &str) -> usize {
     input
         .split("\n\n")
         .map(|s| {
-            let mut machine = claw_machine::ClawMachine::from(s);
+            let mut machine: claw_machine::ClawMachine = s.parse().unwrap();
             machine.move_prize_position(10000000000000, 10000000000000);
             machine
         })
-        .filter_map(|machine| machine.get_minimum_tokens())
+        .filter_map(|machine| machine.get_minimum_tokens(&claw_machine::Rules::default()))
         .sum()
 }
 
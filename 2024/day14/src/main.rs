@@ -1,4 +1,6 @@
-use room::Room;
+use std::env;
+
+use room::{Room, Spread};
 
 mod coord;
 mod robot;
@@ -6,28 +8,73 @@ mod room;
 
 const INPUT: &str = include_str!("../input.txt");
 
-fn part_1(input: &str, width: usize, height: usize) -> usize {
-    let mut room = Room::new(input, width, height);
+fn part_1(input: &str) -> usize {
+    let mut room: Room = input.parse().unwrap();
     room.pass_time(100);
     room.get_safety_factor()
 }
 
-fn part_2(input: &str, width: usize, height: usize) -> usize {
-    let mut room = Room::new(input, width, height);
-    let mut count = 0;
-    while !room.is_easter_egg() {
-        room.pass_time(1);
-        count += 1;
+fn part_2(input: &str) -> usize {
+    let room: Room = input.parse().unwrap();
+    room.find_easter_egg(Spread::Variance)
+        .expect("The robots never bunch up on both axes at once")
+}
+
+/// Looks over a whole period for the seconds where the robots stand out
+fn describe(room: &Room) -> String {
+    let period = room.get_period();
+    let safety_factors: Vec<usize> = room
+        .get_quadrant_series(0..period)
+        .iter()
+        .map(|quadrants| quadrants.iter().product())
+        .collect();
+    let safest = (0..period).min_by_key(|&seconds| safety_factors[seconds]);
+    let apart: Vec<usize> = (0..period)
+        .filter(|&seconds| room.get_overlaps_at(seconds).shared == 0)
+        .collect();
+
+    let mut output = format!("Everything repeats every {period} seconds\n");
+    if let Some(seconds) = safest {
+        output += &format!(
+            "Lowest safety factor is {} after {seconds} seconds\n",
+            safety_factors[seconds]
+        );
     }
-    println!("{}", room);
-    count
+    output += &format!("No robots share a tile after {apart:?} seconds\n");
+    output
 }
 
 fn main() {
-    println!("Part 1: {:?}", part_1(INPUT, 101, 103));
-    println!("Part 2: {:?}", part_2(INPUT, 101, 103));
-}
+    let args: Vec<String> = env::args().collect();
+    let mut room: Room = INPUT.parse().unwrap();
+    match args.get(1).map(|s| s.as_str()) {
+        Some("describe") => {
+            print!("{}", describe(&room));
+            return;
+        }
+        // Draws the easter egg instead, optionally found by `entropy`
+        Some(format @ ("pbm" | "pgm" | "ascii")) => {
+            let spread = match args.get(2).map(|s| s.as_str()) {
+                Some("entropy") => Spread::Entropy,
+                _ => Spread::Variance,
+            };
+            let seconds = room
+                .find_easter_egg(spread)
+                .expect("The robots never bunch up on both axes at once");
+            room.pass_time(seconds);
+            match format {
+                "pbm" => print!("{}", room.to_pbm()),
+                "pgm" => print!("{}", room.to_pgm()),
+                _ => print!("{}", room),
+            }
+            return;
+        }
+        _ => {}
+    }
 
+    println!("Part 1: {:?}", part_1(INPUT));
+    println!("Part 2: {:?}", part_2(INPUT));
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,6 +82,7 @@ mod tests {
     #[test]
     fn test_example() {
         let example = "\
+size=11,7
 p=0,4 v=3,-3
 p=6,3 v=-1,-3
 p=10,3 v=-1,2
@@ -47,6 +95,13 @@ p=9,3 v=2,3
 p=7,3 v=-1,2
 p=2,4 v=2,-3
 p=9,5 v=-3,-3";
-        assert_eq!(part_1(example, 11, 7), 12);
+        assert_eq!(part_1(example), 12);
+    }
+
+    #[test]
+    fn test_easter_egg() {
+        let room: Room = INPUT.parse().unwrap();
+        assert_eq!(Some(7753), room.find_easter_egg(Spread::Variance));
+        assert_eq!(Some(7753), room.find_easter_egg(Spread::Entropy));
     }
 }
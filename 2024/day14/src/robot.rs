@@ -8,18 +8,19 @@ pub struct Robot {
 
 impl Robot {
     pub fn pass_time(&mut self, seconds: usize, max_x: usize, max_y: usize) {
-        let mut x = (self.position.x + self.velocity.x * seconds as isize) % max_x as isize;
-        let mut y = (self.position.y + self.velocity.y * seconds as isize) % max_y as isize;
-
-        if x < 0 {
-            x += max_x as isize;
-        }
+        self.position = self.get_position_after(seconds, max_x, max_y);
+    }
 
-        if y < 0 {
-            y += max_y as isize;
+    /// Where the robot will be after some time, without moving it. Each axis wraps, so only the
+    /// seconds left over from whole trips around it matter, which keeps long times from
+    /// overflowing.
+    pub fn get_position_after(&self, seconds: usize, max_x: usize, max_y: usize) -> Coord {
+        let seconds_x = (seconds % max_x) as isize;
+        let seconds_y = (seconds % max_y) as isize;
+        Coord {
+            x: (self.position.x + self.velocity.x * seconds_x).rem_euclid(max_x as isize),
+            y: (self.position.y + self.velocity.y * seconds_y).rem_euclid(max_y as isize),
         }
-
-        self.position = Coord { x, y }
     }
 }
 
@@ -56,4 +57,12 @@ mod tests {
         robot.pass_time(1, 11, 7);
         assert_eq!(robot.position.to_tuple(), (1, 3));
     }
+
+    #[test]
+    fn test_long_time() {
+        let robot: Robot = "p=2,4 v=2,-3".into();
+        let seconds = usize::MAX / 2;
+        let expected = robot.get_position_after(seconds % 77, 11, 7);
+        assert_eq!(expected, robot.get_position_after(seconds, 11, 7));
+    }
 }
@@ -1,6 +1,24 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+    str::FromStr,
+};
 
-use crate::robot::Robot;
+use crate::{coord::Coord, robot::Robot};
+
+/// The size of the room in the puzzle, used when the input doesn't give one
+const DEFAULT_SIZE: (usize, usize) = (101, 103);
+
+/// How the robots pile onto the same tiles at some moment
+#[derive(Debug, PartialEq, Eq)]
+pub struct Overlaps {
+    /// Tiles with at least one robot on
+    pub occupied: usize,
+    /// Tiles with more than one robot on
+    pub shared: usize,
+    /// The most robots on any one tile
+    pub max_stack: usize,
+}
 
 #[derive(Debug)]
 pub struct Room {
@@ -24,13 +42,34 @@ impl Room {
         }
     }
 
+    /// Where every robot will be after some time. Each robot moves in a straight line that wraps
+    /// around, so this is worked out directly however far ahead it is.
+    pub fn get_positions_at(&self, seconds: usize) -> Vec<Coord> {
+        self.robots
+            .iter()
+            .map(|robot| robot.get_position_after(seconds, self.width, self.height))
+            .collect()
+    }
+
+    /// How long until every robot is back where it started. The x positions repeat every
+    /// `width` seconds and the y positions every `height`, so it's the lcm of the two.
+    pub fn get_period(&self) -> usize {
+        self.width / gcd(self.width, self.height) * self.height
+    }
+
     pub fn get_safety_factor(&self) -> usize {
+        self.get_quadrant_counts_at(0).iter().product()
+    }
+
+    /// Counts the robots in the top left, top right, bottom left and bottom right quadrants,
+    /// leaving out any on the middle row or column
+    pub fn get_quadrant_counts_at(&self, seconds: usize) -> [usize; 4] {
         let mid_x = self.width as isize / 2;
         let mid_y = self.height as isize / 2;
         let mut quadrants = [0, 0, 0, 0];
 
-        for robot in &self.robots {
-            match robot.position.to_tuple() {
+        for position in self.get_positions_at(seconds) {
+            match position.to_tuple() {
                 (x, y) if x < mid_x && y < mid_y => quadrants[0] += 1,
                 (x, y) if x > mid_x && y < mid_y => quadrants[1] += 1,
                 (x, y) if x < mid_x && y > mid_y => quadrants[2] += 1,
@@ -39,34 +78,190 @@ impl Room {
             }
         }
 
-        quadrants.iter().product()
+        quadrants
+    }
+
+    /// The quadrant counts for each second in a range
+    pub fn get_quadrant_series(&self, seconds: Range<usize>) -> Vec<[usize; 4]> {
+        seconds
+            .map(|seconds| self.get_quadrant_counts_at(seconds))
+            .collect()
+    }
+
+    pub fn get_overlaps_at(&self, seconds: usize) -> Overlaps {
+        let counts = self.get_counts(&self.get_positions_at(seconds));
+        let counts = counts.iter().flatten().filter(|&&count| count > 0);
+
+        Overlaps {
+            occupied: counts.clone().count(),
+            shared: counts.clone().filter(|&&count| count > 1).count(),
+            max_stack: counts.max().copied().unwrap_or(0),
+        }
+    }
+
+    /// Finds how many seconds from now the robots are most bunched together, which is when they
+    /// draw the easter egg. The x positions repeat every `width` seconds and the y positions every
+    /// `height`, so each axis is scored on its own over one period and the two best seconds are
+    /// combined with the CRT.
+    pub fn find_easter_egg(&self, spread: Spread) -> Option<usize> {
+        let best_x = self.get_tightest_second(spread, self.width, |coord| coord.x);
+        let best_y = self.get_tightest_second(spread, self.height, |coord| coord.y);
+        crt(best_x, self.width, best_y, self.height)
     }
 
-    pub fn is_easter_egg(&self) -> bool {
-        let mid_x_1 = self.width as isize / 3;
-        let mid_x_2 = mid_x_1 * 2;
-        let mid_y_1 = self.height as isize / 3;
-        let mid_y_2 = mid_y_1 * 2;
-        let robots_near_middle = self
-            .robots
+    /// Draws the room as a plain PBM image, with a black pixel wherever there's a robot
+    pub fn to_pbm(&self) -> String {
+        let grid = self.get_counts(&self.get_positions_at(0));
+        let pixels = grid
             .iter()
-            .filter(|r| {
-                r.position.x > mid_x_1
-                    && r.position.x < mid_x_2
-                    && r.position.y > mid_y_1
-                    && r.position.y < mid_y_2
+            .map(|row| row.iter().map(|&count| usize::from(count > 0)).collect())
+            .collect();
+        format!("P1\n{} {}\n{}", self.width, self.height, to_raster(pixels))
+    }
+
+    /// Draws the room as a plain PGM image, brighter the more robots there are on a tile
+    pub fn to_pgm(&self) -> String {
+        let grid = self.get_counts(&self.get_positions_at(0));
+        let max = grid.iter().flatten().copied().max().unwrap_or(0).max(1);
+        format!(
+            "P2\n{} {}\n{max}\n{}",
+            self.width,
+            self.height,
+            to_raster(grid)
+        )
+    }
+
+    fn get_counts(&self, positions: &[Coord]) -> Vec<Vec<usize>> {
+        let mut grid = vec![vec![0; self.width]; self.height];
+        for position in positions {
+            grid[position.y as usize][position.x as usize] += 1;
+        }
+        grid
+    }
+
+    fn get_tightest_second<F>(&self, spread: Spread, period: usize, axis: F) -> usize
+    where
+        F: Fn(&Coord) -> isize,
+    {
+        let scores: Vec<f64> = (0..period)
+            .map(|seconds| {
+                let values: Vec<usize> = self
+                    .robots
+                    .iter()
+                    .map(|r| axis(&r.get_position_after(seconds, self.width, self.height)) as usize)
+                    .collect();
+                spread.get_score(&values, period)
             })
-            .count();
-        robots_near_middle > self.robots.len() / 2
+            .collect();
+
+        (0..period)
+            .min_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+            .unwrap_or(0)
+    }
+}
+
+/// How to score how spread out the robots are along one axis. The lower the score, the more
+/// they're bunched together.
+#[derive(Debug, Clone, Copy)]
+pub enum Spread {
+    Variance,
+    /// The Shannon entropy of how many robots are in each row or column
+    Entropy,
+}
+
+impl Spread {
+    fn get_score(&self, values: &[usize], size: usize) -> f64 {
+        let n = values.len() as f64;
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        match self {
+            Spread::Variance => {
+                let mean = values.iter().sum::<usize>() as f64 / n;
+                values
+                    .iter()
+                    .map(|&value| (value as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / n
+            }
+            Spread::Entropy => {
+                let mut counts = vec![0; size];
+                for &value in values {
+                    counts[value] += 1;
+                }
+                counts
+                    .iter()
+                    .filter(|&&count| count > 0)
+                    .map(|&count| {
+                        let p = count as f64 / n;
+                        -p * p.log2()
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Finds the first second that's `a` mod `m` and `b` mod `n`, by stepping through the seconds
+/// that are `a` mod `m` until one fits, which is quick for room-sized moduli
+fn crt(a: usize, m: usize, b: usize, n: usize) -> Option<usize> {
+    (0..n)
+        .map(|k| a + k * m)
+        .find(|seconds| seconds % n == b % n)
+}
+
+/// Writes out the rows of a plain PBM or PGM image, wrapping so no line is over 70 characters
+fn to_raster(grid: Vec<Vec<usize>>) -> String {
+    let width = grid.iter().flatten().max().unwrap_or(&0).to_string().len() + 1;
+    let mut raster = String::new();
+    for row in grid {
+        for line in row.chunks(70 / width) {
+            let line: Vec<String> = line.iter().map(|value| value.to_string()).collect();
+            raster += &line.join(" ");
+            raster += "\n";
+        }
+    }
+    raster
+}
+
+impl FromStr for Room {
+    type Err = String;
+
+    /// Reads the robots, one per line, optionally after a line like `size=11,7` giving the
+    /// width and height of the room
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(header) = s.lines().next().and_then(|line| line.strip_prefix("size=")) else {
+            return Ok(Room::new(s, DEFAULT_SIZE.0, DEFAULT_SIZE.1));
+        };
+
+        let error = || format!("Room size should be like size=11,7: {header}");
+        let (width, height) = header.split_once(',').ok_or_else(error)?;
+        let width: usize = width.trim().parse().map_err(|_| error())?;
+        let height: usize = height.trim().parse().map_err(|_| error())?;
+        if width == 0 || height == 0 {
+            return Err(error());
+        }
+
+        let robots = s.split_once('\n').map_or("", |(_, robots)| robots);
+        Ok(Room::new(robots, width, height))
     }
 }
 
 impl Display for Room {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut grid = vec![vec![false; self.width]; self.height];
 
-        for robot in &self.robots {
-            grid[robot.position.y as usize][robot.position.x as usize] = true;
+        for position in self.get_positions_at(0) {
+            grid[position.y as usize][position.x as usize] = true;
         }
 
         for row in grid {
@@ -79,3 +274,80 @@ impl Display for Room {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(0.0, Spread::Variance.get_score(&[3, 3, 3], 5));
+        assert_eq!(1.0, Spread::Variance.get_score(&[1, 3], 5));
+        assert_eq!(0.0, Spread::Entropy.get_score(&[3, 3, 3], 5));
+        assert_eq!(2.0, Spread::Entropy.get_score(&[0, 1, 2, 3], 5));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some(8), crt(2, 3, 3, 5));
+        assert_eq!(Some(7753), crt(7753 % 101, 101, 7753 % 103, 103));
+        assert_eq!(None, crt(1, 4, 2, 6));
+    }
+
+    #[test]
+    fn test_find_easter_egg() {
+        // Every robot gathers on (2, 1) after 4 seconds, and never lines up otherwise
+        let room = Room::new("p=0,2 v=3,2\np=4,2 v=2,2\np=1,0 v=4,1", 5, 3);
+        assert_eq!(Some(4), room.find_easter_egg(Spread::Variance));
+        assert_eq!(Some(4), room.find_easter_egg(Spread::Entropy));
+    }
+
+    #[test]
+    fn test_closed_form() {
+        let input = "size=11,7\np=2,4 v=2,-3\np=0,0 v=1,3";
+        let mut room: Room = input.parse().unwrap();
+        assert_eq!(77, room.get_period());
+
+        let positions = room.get_positions_at(1234);
+        room.pass_time(1234);
+        assert_eq!(room.get_positions_at(0), positions);
+        assert_eq!(room.get_positions_at(77), positions);
+    }
+
+    #[test]
+    fn test_quadrants_and_overlaps() {
+        let room: Room = "size=3,3\np=0,0 v=2,0\np=2,2 v=0,0".parse().unwrap();
+        assert_eq!(
+            vec![[1, 0, 0, 1], [0, 1, 0, 1], [0, 0, 0, 1]],
+            room.get_quadrant_series(0..3)
+        );
+
+        let room: Room = "size=3,3\np=0,0 v=1,1\np=1,1 v=0,0\np=2,2 v=2,2"
+            .parse()
+            .unwrap();
+        let overlaps = Overlaps {
+            occupied: 1,
+            shared: 1,
+            max_stack: 3,
+        };
+        assert_eq!(overlaps, room.get_overlaps_at(1));
+        assert_eq!(3, room.get_overlaps_at(0).occupied);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(
+            101 * 103,
+            "p=0,0 v=1,1".parse::<Room>().unwrap().get_period()
+        );
+        assert!("size=11\np=0,0 v=1,1".parse::<Room>().is_err());
+        assert!("size=0,7\np=0,0 v=1,1".parse::<Room>().is_err());
+    }
+
+    #[test]
+    fn test_images() {
+        let room = Room::new("p=0,0 v=0,0\np=0,0 v=0,0\np=2,1 v=0,0", 3, 2);
+        assert_eq!("P1\n3 2\n1 0 0\n0 0 1\n", room.to_pbm());
+        assert_eq!("P2\n3 2\n2\n2 0 0\n0 0 1\n", room.to_pgm());
+    }
+}